@@ -0,0 +1,41 @@
+use std::process::Command;
+
+fn main() {
+    let git_hash = std::env::var("GIT_HASH").ok().unwrap_or_else(|| {
+        command_output(Command::new("git").args(["rev-parse", "--short", "HEAD"]))
+            .unwrap_or_else(|| "unknown".to_owned())
+    });
+
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_owned());
+    let rustc_version = command_output(Command::new(rustc).arg("--version"))
+        .unwrap_or_else(|| "unknown".to_owned());
+
+    let target = std::env::var("TARGET").unwrap_or_else(|_| "unknown".to_owned());
+
+    // NOTE: cargo exposes enabled features as `CARGO_FEATURE_<NAME>` variables
+    let mut features = std::env::vars()
+        .filter_map(|(name, _)| {
+            let feature = name.strip_prefix("CARGO_FEATURE_")?;
+            Some(feature.to_lowercase().replace('_', "-"))
+        })
+        .collect::<Vec<_>>();
+    features.sort();
+    let features = features.join(",");
+
+    println!("cargo:rustc-env=GIT_HASH={git_hash}");
+    println!("cargo:rustc-env=RUSTC_VERSION={rustc_version}");
+    println!("cargo:rustc-env=TARGET={target}");
+    println!("cargo:rustc-env=CARGO_FEATURES={features}");
+    println!("cargo:rerun-if-env-changed=GIT_HASH");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+}
+
+fn command_output(command: &mut Command) -> Option<String> {
+    let output = command.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let output = String::from_utf8(output.stdout).ok()?;
+    Some(output.trim().to_owned())
+}
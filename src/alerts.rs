@@ -0,0 +1,285 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::config::*;
+use crate::metrics_buffer::parse_labels;
+use crate::service::*;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Evaluates alert rules against the rendered metrics and calls webhooks
+pub struct AlertEvaluator {
+    client: reqwest::Client,
+    rules: Vec<AlertRule>,
+    interval: Duration,
+    /// `(rule index, series)` pairs which already fired
+    firing: HashSet<(usize, String)>,
+}
+
+impl AlertEvaluator {
+    pub fn new(rules: Vec<AlertRule>, interval: Duration) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .context("Failed to build alerts client")?;
+
+        Ok(Self {
+            client,
+            rules,
+            interval,
+            firing: Default::default(),
+        })
+    }
+
+    pub async fn run(mut self, service: Arc<Service>) {
+        log::info!("Evaluating {} alert rules", self.rules.len());
+
+        let mut interval = tokio::time::interval(self.interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            interval.tick().await;
+
+            // NOTE: render before awaiting, metrics hold the listeners lock
+            let metrics = service.metrics().to_string();
+            for alert in self.evaluate(&metrics) {
+                let url = &self.rules[alert.rule].webhook_url;
+                if let Err(e) = self.notify(url, &alert).await {
+                    log::error!("Failed to send alert for {}: {e:?}", alert.metric);
+                }
+            }
+        }
+    }
+
+    /// Returns samples which started to satisfy their rules since the previous call
+    fn evaluate<'a>(&mut self, metrics: &'a str) -> Vec<Alert<'a>> {
+        let mut alerts = Vec::new();
+        let mut firing = HashSet::new();
+
+        for sample in metrics.lines().filter_map(parse_sample) {
+            for (index, rule) in self.rules.iter().enumerate() {
+                if rule.metric != sample.name
+                    || !rule.condition.is_met(sample.value, rule.threshold)
+                {
+                    continue;
+                }
+
+                let key = (index, sample.series.to_owned());
+                if !self.firing.contains(&key) {
+                    alerts.push(Alert {
+                        rule: index,
+                        metric: sample.name,
+                        labels: match sample.series.split_once('{') {
+                            Some((_, labels)) => parse_labels(labels).collect(),
+                            None => Default::default(),
+                        },
+                        value: sample.value,
+                        condition: rule.condition,
+                        threshold: rule.threshold,
+                    });
+                }
+                firing.insert(key);
+            }
+        }
+
+        // NOTE: resolved samples will fire again
+        self.firing = firing;
+        alerts
+    }
+
+    async fn notify(&self, url: &str, alert: &Alert<'_>) -> Result<()> {
+        self.client
+            .post(url)
+            .json(alert)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Webhook payload
+#[derive(Serialize)]
+struct Alert<'a> {
+    #[serde(skip)]
+    rule: usize,
+    metric: &'a str,
+    labels: BTreeMap<&'a str, Cow<'a, str>>,
+    value: f64,
+    condition: Condition,
+    threshold: f64,
+}
+
+struct Sample<'a> {
+    name: &'a str,
+    /// Metric name with labels
+    series: &'a str,
+    value: f64,
+}
+
+/// Parses `name{label="value",...} value`. Skips comments and non-numeric values
+fn parse_sample(line: &str) -> Option<Sample<'_>> {
+    if line.starts_with('#') {
+        return None;
+    }
+
+    let (series, value) = line.rsplit_once(' ')?;
+    let name = match series.find('{') {
+        Some(end) => &series[..end],
+        None => series,
+    };
+
+    Some(Sample {
+        name,
+        series,
+        value: value.parse().ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluator(rules: &[(&str, Condition, f64)]) -> AlertEvaluator {
+        let rules = rules
+            .iter()
+            .map(|(metric, condition, threshold)| AlertRule {
+                metric: metric.to_string(),
+                condition: *condition,
+                threshold: *threshold,
+                webhook_url: "http://127.0.0.1:1/alerts".to_owned(),
+            })
+            .collect();
+        AlertEvaluator::new(rules, Duration::from_secs(1)).unwrap()
+    }
+
+    fn fired<'a>(alerts: &'a [Alert<'_>]) -> Vec<(usize, Vec<(&'a str, &'a str)>)> {
+        alerts
+            .iter()
+            .map(|alert| {
+                let labels = alert.labels.iter().map(|(k, v)| (*k, v.as_ref())).collect();
+                (alert.rule, labels)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fires_once_per_series() {
+        let mut evaluator = evaluator(&[("balance", Condition::Lt, 10.0)]);
+        let metrics = "balance{vault=\"a\"} 5\nbalance{vault=\"b\"} 5\nbalance{vault=\"c\"} 50\n";
+
+        let alerts = evaluator.evaluate(metrics);
+        assert_eq!(
+            fired(&alerts),
+            [(0, vec![("vault", "a")]), (0, vec![("vault", "b")])]
+        );
+        assert_eq!(alerts[0].metric, "balance");
+        assert_eq!(alerts[0].value, 5.0);
+
+        // Still firing
+        assert!(evaluator.evaluate(metrics).is_empty());
+        // A new series fires while the old ones stay silent
+        let metrics = format!("{metrics}balance{{vault=\"d\"}} 1\n");
+        let alerts = evaluator.evaluate(&metrics);
+        assert_eq!(fired(&alerts), [(0, vec![("vault", "d")])]);
+    }
+
+    #[test]
+    fn alert_labels_are_unescaped() {
+        let mut evaluator = evaluator(&[("balance", Condition::Lt, 10.0)]);
+        let metrics = "balance{symbol=\"a\\\"b\",vault=\"c\"} 5\n";
+
+        let alerts = evaluator.evaluate(metrics);
+        assert_eq!(
+            fired(&alerts),
+            [(0, vec![("symbol", "a\"b"), ("vault", "c")])]
+        );
+    }
+
+    #[test]
+    fn fires_again_after_resolving() {
+        let mut evaluator = evaluator(&[("balance", Condition::Lt, 10.0)]);
+        let low = "balance{vault=\"a\"} 5\n";
+        let high = "balance{vault=\"a\"} 50\n";
+
+        assert_eq!(evaluator.evaluate(low).len(), 1);
+        assert!(evaluator.evaluate(low).is_empty());
+        assert!(evaluator.evaluate(high).is_empty());
+        assert_eq!(evaluator.evaluate(low).len(), 1);
+
+        // Missing samples are resolved too
+        assert!(evaluator.evaluate("").is_empty());
+        assert_eq!(evaluator.evaluate(low).len(), 1);
+    }
+
+    #[test]
+    fn rules_fire_independently() {
+        let mut evaluator = evaluator(&[
+            ("balance", Condition::Lt, 10.0),
+            ("balance", Condition::Gt, 1.0),
+            ("total_assets", Condition::Gt, 0.0),
+        ]);
+
+        let alerts = evaluator.evaluate("balance 5\n");
+        assert_eq!(fired(&alerts), [(0, vec![]), (1, vec![])]);
+        assert_eq!(alerts[1].condition, Condition::Gt);
+        assert_eq!(alerts[1].threshold, 1.0);
+    }
+
+    #[test]
+    fn skips_comments() {
+        let mut evaluator = evaluator(&[("balance", Condition::Gte, 0.0)]);
+        let metrics = "# HELP balance ERC-20 token balance held by the vault\n\
+                       # TYPE balance gauge\n\
+                       # UNIT balance tokens\n\
+                       balance{vault=\"a\"} 1\n\
+                       # EOF\n";
+
+        assert_eq!(
+            fired(&evaluator.evaluate(metrics)),
+            [(0, vec![("vault", "a")])]
+        );
+        assert!(parse_sample("# HELP balance 1").is_none());
+        assert!(parse_sample("# TYPE balance gauge").is_none());
+        assert!(parse_sample("# EOF").is_none());
+    }
+
+    #[test]
+    fn parses_samples() {
+        let sample = parse_sample("balance{chain_id=\"1\",vault=\"0xab\"} 1.5").unwrap();
+        assert_eq!(sample.name, "balance");
+        assert_eq!(sample.series, "balance{chain_id=\"1\",vault=\"0xab\"}");
+        assert_eq!(sample.value, 1.5);
+
+        let sample =
+            parse_sample("uptime_seconds 340282366920938463463374607431768211455").unwrap();
+        assert_eq!(sample.name, "uptime_seconds");
+        assert_eq!(sample.series, "uptime_seconds");
+        assert_eq!(sample.value, u128::MAX as f64);
+
+        assert!(parse_sample("").is_none());
+        assert!(parse_sample("balance").is_none());
+        assert!(parse_sample("balance NaN-ish").is_none());
+    }
+
+    #[test]
+    fn conditions_at_threshold() {
+        let cases = [
+            (Condition::Gt, [false, false, true]),
+            (Condition::Gte, [false, true, true]),
+            (Condition::Lt, [true, false, false]),
+            (Condition::Lte, [true, true, false]),
+            (Condition::Eq, [false, true, false]),
+            (Condition::Ne, [true, false, true]),
+        ];
+        for (condition, expected) in cases {
+            let actual = [9.999, 10.0, 10.001].map(|value| condition.is_met(value, 10.0));
+            assert_eq!(actual, expected, "{condition:?}");
+        }
+    }
+}
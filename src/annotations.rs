@@ -0,0 +1,434 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::config::MetricsFormat;
+
+/// Groups rendered metrics by family and prepends `# HELP` and `# TYPE` lines.
+/// OpenMetrics output additionally gets `# UNIT` lines and the `# EOF` terminator
+pub struct Annotated<T>(pub T, pub MetricsFormat);
+
+impl<T: std::fmt::Display> std::fmt::Display for Annotated<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rendered = self.0.to_string();
+
+        let mut families = Vec::<(&str, Vec<&str>)>::new();
+        let mut family_indices = HashMap::<&str, usize>::new();
+        for line in rendered.lines() {
+            if line.is_empty() {
+                continue;
+            }
+
+            let name = match line.find(['{', ' ']) {
+                Some(end) => &line[..end],
+                None => line,
+            };
+            let family = family_name(name);
+
+            let index = *family_indices.entry(family).or_insert_with(|| {
+                families.push((family, Vec::new()));
+                families.len() - 1
+            });
+            families[index].1.push(line);
+        }
+
+        for (family, lines) in families {
+            match (describe(family), self.1) {
+                (Some((kind, help)), MetricsFormat::Prometheus) => {
+                    writeln!(f, "# HELP {family} {help}")?;
+                    writeln!(f, "# TYPE {family} {kind}")?;
+                }
+                (Some((kind, help)), MetricsFormat::OpenMetrics) => {
+                    // NOTE: OpenMetrics counter samples have the `_total` suffix,
+                    // but the family name doesn't
+                    let family = match kind {
+                        COUNTER => family.strip_suffix("_total").unwrap_or(family),
+                        _ => family,
+                    };
+                    writeln!(f, "# TYPE {family} {kind}")?;
+                    if let Some(unit) = unit(family) {
+                        writeln!(f, "# UNIT {family} {unit}")?;
+                    }
+                    writeln!(f, "# HELP {family} {help}")?;
+                }
+                (None, _) => {}
+            }
+            for line in lines {
+                f.write_str(line)?;
+                f.write_char('\n')?;
+            }
+        }
+
+        if self.1 == MetricsFormat::OpenMetrics {
+            f.write_str("# EOF\n")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Strips histogram suffixes
+fn family_name(name: &str) -> &str {
+    for suffix in ["_bucket", "_sum", "_count"] {
+        if let Some(base) = name.strip_suffix(suffix) {
+            if matches!(describe(base), Some((HISTOGRAM, _))) {
+                return base;
+            }
+        }
+    }
+    name
+}
+
+/// OpenMetrics requires the unit to be a suffix of the family name
+fn unit(family: &str) -> Option<&'static str> {
+    family.ends_with("_seconds").then_some("seconds")
+}
+
+fn describe(family: &str) -> Option<(&'static str, &'static str)> {
+    METRIC_FAMILIES
+        .iter()
+        .find(|(name, _, _)| *name == family)
+        .map(|(_, kind, help)| (*kind, *help))
+}
+
+const GAUGE: &str = "gauge";
+const COUNTER: &str = "counter";
+const HISTOGRAM: &str = "histogram";
+
+/// (name, type, help)
+static METRIC_FAMILIES: &[(&str, &str, &str)] = &[
+    (
+        "bridge_vaults_exporter_build_info",
+        GAUGE,
+        "Exporter version, git commit and rustc version",
+    ),
+    (
+        "bridge_vaults_exporter_info",
+        GAUGE,
+        "Exporter version, build target and enabled cargo features",
+    ),
+    (
+        "bridge_vaults_exporter_start_time_seconds",
+        GAUGE,
+        "Unix timestamp of the exporter start",
+    ),
+    ("token_decimals", GAUGE, "Token decimals"),
+    ("config_reload_total", COUNTER, "Number of config reloads"),
+    ("relay_round", GAUGE, "Current relay round"),
+    ("relay_count", GAUGE, "Relay count in the current round"),
+    (
+        "relay_round_expires_at",
+        GAUGE,
+        "Timestamp when the current relay round expires",
+    ),
+    (
+        "relay_round_created_at",
+        GAUGE,
+        "Block timestamp of the latest NewRound event",
+    ),
+    (
+        "relay_set_changes_total",
+        COUNTER,
+        "Number of relay count changes between rounds",
+    ),
+    (
+        "staking_total_staked",
+        GAUGE,
+        "Total amount staked by relays",
+    ),
+    ("staking_min_stake", GAUGE, "Minimal relay stake"),
+    ("staking_slash_count", GAUGE, "Number of relay slashings"),
+    (
+        "governance_proposal_count",
+        GAUGE,
+        "Number of created governance proposals",
+    ),
+    (
+        "governance_quorum_votes",
+        GAUGE,
+        "Number of votes required for a proposal quorum",
+    ),
+    (
+        "governance_active_proposals",
+        GAUGE,
+        "Number of governance proposals open for voting",
+    ),
+    (
+        "bridge_exporter_poll_duration_seconds",
+        GAUGE,
+        "Duration of the most recent poll cycle over all vaults in the network",
+    ),
+    (
+        "bridge_exporter_poll_errors_total",
+        COUNTER,
+        "Number of failed vault updates in the network",
+    ),
+    (
+        "network_latest_block",
+        GAUGE,
+        "Latest block number of the node",
+    ),
+    (
+        "network_block_updated_at",
+        GAUGE,
+        "Timestamp when the latest block number was last changed",
+    ),
+    ("network_gas_price_gwei", GAUGE, "Node gas price in gwei"),
+    (
+        "network_base_fee_per_gas_wei",
+        GAUGE,
+        "Base fee of the latest block in wei",
+    ),
+    ("network_peer_count", GAUGE, "Number of peers of the node"),
+    (
+        "network_reorg_detected_total",
+        COUNTER,
+        "Number of detected chain reorganizations",
+    ),
+    ("network_syncing", GAUGE, "Whether the node is syncing"),
+    (
+        "network_sync_current_block",
+        GAUGE,
+        "Highest block synced by the node",
+    ),
+    (
+        "network_sync_highest_block",
+        GAUGE,
+        "Estimated highest block of the network",
+    ),
+    (
+        "network_node_errors_total",
+        COUNTER,
+        "Number of failed node state requests",
+    ),
+    (
+        "vault_update_error_total",
+        COUNTER,
+        "Number of failed vault updates",
+    ),
+    (
+        "vault_update_last_error_timestamp",
+        GAUGE,
+        "Timestamp of the last failed vault update",
+    ),
+    (
+        "update_duration_seconds",
+        HISTOGRAM,
+        "Vault update duration in seconds",
+    ),
+    (
+        "vault_stale_reads_total",
+        COUNTER,
+        "Number of metric collections which exported a stale vault state",
+    ),
+    ("balance", GAUGE, "ERC-20 token balance held by the vault"),
+    (
+        "vault_weighted_balance",
+        GAUGE,
+        "Vault balance multiplied by the vault weight",
+    ),
+    (
+        "vault_tvl_weight",
+        GAUGE,
+        "Configured share of the vault in the consolidated token TVL",
+    ),
+    (
+        "balance_ema",
+        GAUGE,
+        "Exponential moving average of the vault balance",
+    ),
+    (
+        "total_assets_ema",
+        GAUGE,
+        "Exponential moving average of the vault total assets",
+    ),
+    (
+        "total_assets",
+        GAUGE,
+        "Total vault assets, including funds locked in strategies",
+    ),
+    (
+        "vault_tvl_usd",
+        GAUGE,
+        "Total vault assets in USD using the Chainlink price feed",
+    ),
+    (
+        "vault_utilisation_ratio",
+        GAUGE,
+        "Ratio of the vault balance to its total assets",
+    ),
+    (
+        "withdraw_limit_per_period",
+        GAUGE,
+        "Maximum amount of tokens which can be withdrawn in one withdrawal period",
+    ),
+    (
+        "vault_deposit_limit",
+        GAUGE,
+        "Maximum amount of tokens which can be deposited into the vault",
+    ),
+    (
+        "withdrawal_period_total",
+        GAUGE,
+        "Amount of tokens withdrawn in the current withdrawal period",
+    ),
+    (
+        "withdrawal_period_considered",
+        GAUGE,
+        "Amount of tokens approved for withdrawal in the current withdrawal period",
+    ),
+    (
+        "withdrawal_period_history_total",
+        GAUGE,
+        "Amount of tokens withdrawn in a previous withdrawal period",
+    ),
+    (
+        "withdrawal_period_history_considered",
+        GAUGE,
+        "Amount of tokens approved for withdrawal in a previous withdrawal period",
+    ),
+    (
+        "vault_withdraw_available",
+        GAUGE,
+        "Amount of tokens which can still be withdrawn in the current withdrawal period",
+    ),
+    (
+        "vault_withdraw_utilisation",
+        GAUGE,
+        "Ratio of the amount withdrawn in the current withdrawal period to the limit",
+    ),
+    (
+        "withdrawal_period_remaining",
+        GAUGE,
+        "Seconds until the end of the current withdrawal period",
+    ),
+    (
+        "updated_at",
+        GAUGE,
+        "Deprecated, use last_successful_update_at. Timestamp of the last successful vault update",
+    ),
+    (
+        "last_successful_update_at",
+        GAUGE,
+        "Timestamp of the last successful vault update",
+    ),
+    (
+        "last_attempt_at",
+        GAUGE,
+        "Timestamp of the last vault update attempt, successful or not",
+    ),
+    (
+        "token_total_supply",
+        GAUGE,
+        "Total supply of the vault token",
+    ),
+    ("vault_paused", GAUGE, "Whether the vault is paused"),
+    (
+        "vault_share_price",
+        GAUGE,
+        "Amount of assets for one share (1e18 units)",
+    ),
+    (
+        "vault_deposit_events_total",
+        COUNTER,
+        "Number of vault deposit events",
+    ),
+    (
+        "vault_withdraw_events_total",
+        COUNTER,
+        "Number of vault withdrawal events",
+    ),
+    (
+        "vault_realized_pnl_this_period",
+        GAUGE,
+        "Change of vault total assets since the previous poll excluding deposits and withdrawals",
+    ),
+    (
+        "vault_code_hash_changed",
+        GAUGE,
+        "Whether the vault code has changed since startup",
+    ),
+    (
+        "vault_implementation_address",
+        GAUGE,
+        "EIP-1967 implementation address of the vault proxy",
+    ),
+    (
+        "vault_call_gas_estimate",
+        GAUGE,
+        "Estimated gas of the vault view calls",
+    ),
+    ("vault_creation_block", GAUGE, "Vault deployment block"),
+    (
+        "vault_management_fee_bps",
+        GAUGE,
+        "Vault management fee in basis points",
+    ),
+    ("vault_min_deposit", GAUGE, "Minimum vault deposit amount"),
+    (
+        "vault_min_withdraw",
+        GAUGE,
+        "Minimum vault withdrawal amount",
+    ),
+    (
+        "vault_active_strategy_count",
+        GAUGE,
+        "Number of active strategies in the vault withdrawal queue",
+    ),
+    (
+        "vault_monitored_allowance",
+        GAUGE,
+        "Vault token allowance for the owner/spender pair",
+    ),
+    (
+        "vault_monitored_allowance_below_threshold",
+        GAUGE,
+        "Whether the monitored allowance is below the threshold",
+    ),
+    (
+        "vault_bridge_allowance",
+        GAUGE,
+        "Allowance of the bridge proxy to spend vault tokens",
+    ),
+    ("vault_guardian", GAUGE, "Current vault guardian address"),
+    (
+        "vault_management",
+        GAUGE,
+        "Current vault management address",
+    ),
+    (
+        "vault_governance",
+        GAUGE,
+        "Current vault governance address",
+    ),
+    ("curve_virtual_price", GAUGE, "Curve pool virtual price"),
+    (
+        "curve_coin_balance",
+        GAUGE,
+        "Curve pool balance of the coin",
+    ),
+    (
+        "aave_liquidity_index",
+        GAUGE,
+        "Aave v3 reserve liquidity index of the vault token (in rays)",
+    ),
+    (
+        "aave_current_liquidity_rate",
+        GAUGE,
+        "Aave v3 reserve supply rate of the vault token (in rays)",
+    ),
+    (
+        "compound_exchange_rate",
+        GAUGE,
+        "Compound cToken exchange rate (mantissa as returned by exchangeRateStored)",
+    ),
+    (
+        "compound_total_borrows",
+        GAUGE,
+        "Amount of underlying tokens borrowed from the cToken",
+    ),
+    (
+        "compound_cash",
+        GAUGE,
+        "Amount of underlying tokens held by the cToken",
+    ),
+];
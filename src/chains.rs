@@ -0,0 +1,61 @@
+/// Returns a well-known name of the chain
+pub fn chain_name(chain_id: u32) -> Option<&'static str> {
+    KNOWN_CHAINS
+        .iter()
+        .find(|(id, _)| *id == chain_id)
+        .map(|(_, name)| *name)
+}
+
+/// (chain id, name)
+static KNOWN_CHAINS: &[(u32, &str)] = &[
+    (1, "ethereum"),
+    (5, "goerli"),
+    (10, "optimism"),
+    (14, "flare"),
+    (25, "cronos"),
+    (30, "rootstock"),
+    (40, "telos"),
+    (42, "lukso"),
+    (56, "bsc"),
+    (57, "syscoin"),
+    (61, "ethereum_classic"),
+    (66, "okc"),
+    (97, "bsc_testnet"),
+    (100, "gnosis"),
+    (106, "velas"),
+    (122, "fuse"),
+    (128, "heco"),
+    (137, "polygon"),
+    (204, "opbnb"),
+    (250, "fantom"),
+    (288, "boba"),
+    (314, "filecoin"),
+    (324, "zksync"),
+    (592, "astar"),
+    (1088, "metis"),
+    (1101, "polygon_zkevm"),
+    (1284, "moonbeam"),
+    (1285, "moonriver"),
+    (2000, "dogechain"),
+    (2222, "kava"),
+    (4689, "iotex"),
+    (5000, "mantle"),
+    (7700, "canto"),
+    (8217, "klaytn"),
+    (8453, "base"),
+    (9001, "evmos"),
+    (10000, "smartbch"),
+    (17000, "holesky"),
+    (42161, "arbitrum"),
+    (42170, "arbitrum_nova"),
+    (42220, "celo"),
+    (43114, "avalanche"),
+    (59144, "linea"),
+    (80001, "mumbai"),
+    (81457, "blast"),
+    (534352, "scroll"),
+    (1313161554, "aurora"),
+    (1666600000, "harmony"),
+    (11155111, "sepolia"),
+    (7777777, "zora"),
+];
@@ -1,50 +1,886 @@
+use std::collections::HashSet;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 
-use serde::Deserialize;
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize};
 use web3::types::Address;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct Config {
-    /// Listen address of healthcheck service. Just accepts connections
+    /// Listen address of healthcheck service. Serves `GET /health` and `GET /ready`
     #[serde(default)]
     pub healthcheck_address: Option<SocketAddr>,
 
+    /// Healthcheck service settings
+    #[serde(default)]
+    pub health_settings: HealthSettings,
+
     /// Networks
     pub networks: Vec<NetworkVaults>,
 
+    /// Token metadata which is used instead of the `symbol()` and `decimals()` calls
+    #[serde(default)]
+    pub tokens: Vec<TokenOverride>,
+
     /// Prometheus metrics exporter settings.
-    pub metrics_settings: pomfrit::Config,
+    pub metrics_settings: MetricsSettings,
+
+    /// Optional daily digest of vault metrics
+    #[serde(default)]
+    pub daily_report: Option<DailyReportConfig>,
+
+    /// Optional Prometheus push gateway, for environments which can't be scraped.
+    /// The `/metrics` endpoint is still served
+    #[serde(default)]
+    pub push_gateway: Option<PushGatewayConfig>,
+
+    /// In-process threshold alerts, for setups without Alertmanager
+    #[serde(default)]
+    pub alerts: Vec<AlertRule>,
+
+    /// Source of `${NAME}` substitutions in the config. Default: environment variables
+    #[serde(default)]
+    pub secret_resolver: Option<SecretResolverConfig>,
+
+    /// Log output settings
+    #[serde(default)]
+    pub logging: LoggingSettings,
 
     /// log4rs settings.
     /// See [docs](https://docs.rs/log4rs/1.0.0/log4rs/) for more details
     #[serde(default = "default_logger_settings")]
+    #[schemars(with = "serde_json::Value")]
     pub logger_settings: serde_yaml::Value,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+impl Config {
+    /// Checks config consistency which doesn't require any RPC calls
+    pub fn validate(&self) -> Result<()> {
+        self.metrics_settings.exporter()?;
+        if self.metrics_settings.collection_interval_sec == 0 {
+            anyhow::bail!("Invalid collection interval: 0");
+        }
+        if self.metrics_settings.max_concurrent_polls == 0 {
+            anyhow::bail!("Invalid max concurrent polls: 0");
+        }
+
+        for rewrite in &self.metrics_settings.label_rewrites {
+            if rewrite.label.is_empty() {
+                anyhow::bail!("Empty label rewrite label");
+            }
+        }
+
+        let metrics_address = self.metrics_settings.listen_address;
+        if metrics_address.port() == 0 {
+            anyhow::bail!("Invalid metrics listen address: {metrics_address}");
+        }
+        if self.healthcheck_address == Some(metrics_address) {
+            anyhow::bail!(
+                "Metrics and healthcheck services use the same address: {metrics_address}"
+            );
+        }
+
+        if let Some(push_gateway) = &self.push_gateway {
+            push_gateway.push_url()?;
+            if push_gateway.push_interval_sec == 0 {
+                anyhow::bail!("Invalid push gateway interval: 0");
+            }
+        }
+
+        if let Some(SecretResolverConfig::VaultAgent { secret_path, .. }) = &self.secret_resolver {
+            if secret_path.trim_start_matches('/').is_empty() {
+                anyhow::bail!("Empty Vault secret path");
+            }
+        }
+
+        for rule in &self.alerts {
+            if rule.metric.is_empty() {
+                anyhow::bail!("Empty alert metric");
+            }
+            if !rule.threshold.is_finite() {
+                anyhow::bail!("Invalid alert threshold for {}", rule.metric);
+            }
+            url::Url::parse(&rule.webhook_url)
+                .with_context(|| format!("Invalid alert webhook url: {}", rule.webhook_url))?;
+        }
+
+        let mut tokens = HashSet::new();
+        for token in &self.tokens {
+            if !tokens.insert((token.chain_id, token.address)) {
+                anyhow::bail!(
+                    "Duplicate token override: 0x{:x} (chain_id={})",
+                    token.address,
+                    token.chain_id
+                );
+            }
+        }
+
+        let mut endpoints = HashSet::new();
+        for network in &self.networks {
+            for endpoint in &network.endpoints {
+                if !endpoints.insert(endpoint.endpoint()) {
+                    anyhow::bail!("Duplicate endpoint: {}", endpoint.endpoint());
+                }
+            }
+
+            if network.tls.is_some() && network.endpoints.iter().any(TransportConfig::is_web_socket)
+            {
+                anyhow::bail!("TLS client certificates are not supported for WebSocket endpoints");
+            }
+
+            match network.trigger {
+                Some(Trigger::Interval(0)) => anyhow::bail!("Invalid trigger interval"),
+                Some(Trigger::NewHeads)
+                    if !network.endpoints.iter().any(TransportConfig::is_web_socket) =>
+                {
+                    anyhow::bail!("`new_heads` trigger requires a WebSocket endpoint")
+                }
+                _ => {}
+            }
+
+            let mut bridge_proxies = HashSet::new();
+            for bridge_proxy in &network.bridge_proxies {
+                if !bridge_proxies.insert(bridge_proxy) {
+                    anyhow::bail!("Duplicate bridge proxy: 0x{bridge_proxy:x}");
+                }
+            }
+
+            let mut vaults = HashSet::new();
+            for vault in &network.vaults {
+                if !vaults.insert(vault.address) {
+                    anyhow::bail!("Duplicate vault entry: 0x{:x}", vault.address);
+                }
+                if vault.poll_interval_sec == Some(0) {
+                    anyhow::bail!("Invalid poll interval for vault 0x{:x}", vault.address);
+                }
+                if vault.withdrawal_period_sec == 0 {
+                    anyhow::bail!("Invalid withdrawal period for vault 0x{:x}", vault.address);
+                }
+                if matches!(vault.smoothing_alpha, Some(alpha) if !(alpha > 0.0 && alpha <= 1.0)) {
+                    anyhow::bail!("Invalid smoothing alpha for vault 0x{:x}", vault.address);
+                }
+                if matches!(vault.weight, Some(weight) if !(weight.is_finite() && weight >= 0.0)) {
+                    anyhow::bail!("Invalid weight for vault 0x{:x}", vault.address);
+                }
+                if matches!(&vault.event_log_config, Some(config) if config.lookback_blocks == 0) {
+                    anyhow::bail!("Invalid event lookback for vault 0x{:x}", vault.address);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// NOTE: exporter fields are not flattened into a separate struct, because
+/// flattening breaks coercion of substituted strings (e.g. `"${INTERVAL_SEC}"`)
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct MetricsSettings {
+    /// Listen address of metrics. Default: `0.0.0.0:10000`
+    #[serde(default = "default_metrics_listen_address")]
+    pub listen_address: SocketAddr,
+
+    /// Path to the metrics. Default: `/`
+    #[serde(default = "default_metrics_path")]
+    pub metrics_path: String,
+
+    /// Metrics update interval in seconds. Default: 10
+    #[serde(default = "default_collection_interval_sec")]
+    pub collection_interval_sec: u64,
+
+    /// Bucket boundaries (in seconds) of the `update_duration_seconds` histogram
+    #[serde(default = "default_update_duration_buckets")]
+    pub update_duration_buckets: Vec<f64>,
+
+    /// Delay between starting vault listeners, to avoid bursts of RPC calls at startup
+    #[serde(default)]
+    pub stagger_ms: Option<u64>,
+
+    /// Interval between vault initialization progress summaries. `0` disables them. Default: 10
+    #[serde(default = "default_progress_report_interval_secs")]
+    pub progress_report_interval_secs: u64,
+
+    /// Whether to use EIP-55 mixed-case addresses in metric labels. Default: false.
+    /// NOTE: ignored if `address_format` is specified
+    #[serde(default = "default_checksum_addresses")]
+    pub checksum_addresses: bool,
+
+    /// Format of addresses in metric labels. Default: derived from `checksum_addresses`
+    #[serde(default)]
+    pub address_format: Option<AddressFormat>,
+
+    /// Vault state is considered stale if it was not updated for this
+    /// number of seconds. Default: 300
+    #[serde(default = "default_stale_state_threshold_sec")]
+    pub stale_state_threshold_sec: u32,
+
+    /// Maximum number of simultaneous vault updates across all networks. Default: 16
+    #[serde(default = "default_max_concurrent_polls")]
+    pub max_concurrent_polls: usize,
+
+    /// Maximum number of idle HTTP connections kept per RPC host. HTTP endpoints
+    /// of the same origin share one connection pool. Default: unlimited
+    #[serde(default)]
+    pub max_idle_connections_per_host: Option<usize>,
+
+    /// Metrics output format. Default: `prometheus`
+    #[serde(default)]
+    pub format: MetricsFormat,
+
+    /// Label renames, applied to every exported metric line in order
+    #[serde(default)]
+    pub label_rewrites: Vec<LabelRewrite>,
+}
+
+impl MetricsSettings {
+    pub fn address_format(&self) -> AddressFormat {
+        match self.address_format {
+            Some(format) => format,
+            None if self.checksum_addresses => AddressFormat::Checksum,
+            None => AddressFormat::WithPrefix,
+        }
+    }
+
+    pub fn exporter(&self) -> Result<ExporterConfig> {
+        let metrics_path = self
+            .metrics_path
+            .parse()
+            .with_context(|| format!("Invalid metrics path: {}", self.metrics_path))?;
+        Ok(ExporterConfig {
+            listen_address: self.listen_address,
+            metrics_path,
+            collection_interval_sec: self.collection_interval_sec,
+            format: self.format,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExporterConfig {
+    pub listen_address: SocketAddr,
+    pub metrics_path: hyper::http::uri::PathAndQuery,
+    pub collection_interval_sec: u64,
+    pub format: MetricsFormat,
+}
+
+/// NOTE: the section itself is read before substitution, so it can't use `${NAME}`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum SecretResolverConfig {
+    /// Environment variables
+    Env,
+    /// File with `NAME=value` lines. Empty lines and lines starting with `#` are skipped
+    File { path: PathBuf },
+    /// HashiCorp Vault KV secret, read through the Vault Agent API proxy
+    /// listening on a unix socket. Secret fields are used as names
+    VaultAgent {
+        socket: PathBuf,
+        /// Secret API path, e.g. `secret/data/bridge-vaults-exporter`
+        secret_path: String,
+    },
+}
+
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct LoggingSettings {
+    /// Log lines format. Default: `text`
+    pub log_format: LogFormat,
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// Use encoders from `logger_settings`
+    #[default]
+    Text,
+    /// Write JSON lines to stdout. Only levels are used from `logger_settings`
+    Json,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct HealthSettings {
+    /// `GET /ready` fails if any vault was not updated for this
+    /// number of seconds. Default: 300
+    pub max_stale_secs: u32,
+
+    /// Whether to serve `GET /debug/vaults` with JSON states of all vaults. Default: false
+    pub debug_endpoint: bool,
+}
+
+impl Default for HealthSettings {
+    fn default() -> Self {
+        Self {
+            max_stale_secs: 300,
+            debug_endpoint: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DailyReportConfig {
+    /// Path to the JSON report file. It is overwritten atomically every day
+    pub output_path: PathBuf,
+
+    /// Time of day in UTC when the report is generated (`HH:MM`)
+    pub report_time_utc: String,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PushGatewayConfig {
+    /// Push gateway base URL, e.g. `http://pushgateway:9091`
+    pub url: String,
+
+    /// Value of the `job` grouping key
+    pub job: String,
+
+    /// Interval between pushes. Default: 15
+    #[serde(default = "default_push_interval_sec")]
+    pub push_interval_sec: u64,
+}
+
+impl PushGatewayConfig {
+    /// Returns `<url>/metrics/job/<job>`
+    pub fn push_url(&self) -> Result<url::Url> {
+        if self.job.is_empty() {
+            anyhow::bail!("Empty push gateway job");
+        }
+
+        let mut url = url::Url::parse(&self.url)
+            .with_context(|| format!("Invalid push gateway url: {}", self.url))?;
+        url.path_segments_mut()
+            .map_err(|_| anyhow::anyhow!("Invalid push gateway url: {}", self.url))?
+            .pop_if_empty()
+            .extend(["metrics", "job", &self.job]);
+        Ok(url)
+    }
+}
+
+/// Fires when any sample of the metric satisfies the condition.
+/// The webhook is called once when the sample starts firing
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AlertRule {
+    /// Exported metric name, e.g. `vault_utilisation_ratio`
+    pub metric: String,
+
+    /// Comparison of the sample value with the threshold
+    pub condition: Condition,
+
+    pub threshold: f64,
+
+    /// URL which receives a JSON payload with the firing sample
+    pub webhook_url: String,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    /// `value > threshold`
+    Gt,
+    /// `value >= threshold`
+    Gte,
+    /// `value < threshold`
+    Lt,
+    /// `value <= threshold`
+    Lte,
+    /// `value == threshold`
+    Eq,
+    /// `value != threshold`
+    Ne,
+}
+
+impl Condition {
+    pub fn is_met(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            Self::Gt => value > threshold,
+            Self::Gte => value >= threshold,
+            Self::Lt => value < threshold,
+            Self::Lte => value <= threshold,
+            Self::Eq => value == threshold,
+            Self::Ne => value != threshold,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct NetworkVaults {
-    /// RPC endpoint
-    pub endpoint: String,
+    /// Optional human-readable network name, exported as the `network` label
+    #[serde(default)]
+    pub name: Option<String>,
 
-    /// Optional bridge address. Must be only used once
+    /// RPC endpoints of the same network. The first responding endpoint
+    /// is used, others are used as fallbacks on transport errors
+    #[serde(alias = "endpoint", deserialize_with = "deserialize_endpoints")]
+    #[schemars(with = "EndpointsHelper")]
+    pub endpoints: Vec<TransportConfig>,
+
+    /// Bridge addresses. Each address must be only used once
+    #[serde(
+        default,
+        alias = "bridge_proxy",
+        deserialize_with = "deserialize_bridge_proxies"
+    )]
+    #[schemars(with = "BridgeProxiesHelper")]
+    pub bridge_proxies: Vec<Address>,
+
+    /// Relay staking contracts
     #[serde(default)]
-    pub bridge_proxy: Option<Address>,
+    pub staking: Vec<StakingEntry>,
+
+    /// Optional bridge governance contract
+    #[serde(default)]
+    #[schemars(with = "Option<String>")]
+    pub governance: Option<Address>,
+
+    /// RPC call retries settings
+    #[serde(default)]
+    pub retry: RetryConfig,
+
+    /// Timeout of a single RPC call attempt. Default: 10000
+    #[serde(default = "default_call_timeout_ms")]
+    pub call_timeout_ms: u64,
+
+    /// Whether to send all vault calls as a single JSON-RPC batch. Default: true
+    #[serde(default = "default_use_batching")]
+    pub use_batching: bool,
+
+    /// Whether to export the node peer count using `net_peerCount`.
+    /// Disable for nodes which don't expose it. Default: true
+    #[serde(default = "default_monitor_peer_count")]
+    pub monitor_peer_count: bool,
+
+    /// Whether to compare block hashes between node polls to detect chain reorganizations.
+    /// Vault states are updated again after a reorg. Default: false
+    #[serde(default)]
+    pub reorg_detection: bool,
+
+    /// TLS client certificate for private HTTP RPC endpoints
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+
+    /// What triggers vault updates. Default: polling with `collection_interval_sec`
+    #[serde(default)]
+    pub trigger: Option<Trigger>,
 
     /// Vault addresses
     pub vaults: Vec<VaultsEntry>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct LabelRewrite {
+    /// Metric name to rewrite labels of. Default: all metrics
+    #[serde(default)]
+    pub metric: Option<String>,
+
+    /// Label name
+    pub label: String,
+
+    /// New label name. An empty name drops the label
+    pub replacement: String,
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricsFormat {
+    /// Prometheus text format
+    #[default]
+    Prometheus,
+    /// OpenMetrics text format
+    OpenMetrics,
+}
+
+impl MetricsFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Prometheus => "text/plain; version=0.0.4; charset=utf-8",
+            Self::OpenMetrics => "application/openmetrics-text; version=1.0.0; charset=utf-8",
+        }
+    }
+}
+
+/// Format of addresses in metric labels
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressFormat {
+    /// Lowercase hex with the `0x` prefix
+    WithPrefix,
+    /// Lowercase hex without the `0x` prefix
+    WithoutPrefix,
+    /// EIP-55 mixed-case hex with the `0x` prefix
+    Checksum,
+}
+
+/// Vault updates trigger
+///
+/// ```yaml
+/// trigger: new_heads
+/// # or
+/// trigger:
+///   interval: 30
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Trigger {
+    /// Poll vaults every N seconds
+    Interval(u64),
+    /// Update vaults on each new block. Requires a WebSocket endpoint
+    NewHeads,
+}
+
+/// RPC transport. Can be specified either as a plain URL (the transport
+/// is chosen by the URL scheme) or explicitly:
+///
+/// ```yaml
+/// endpoint:
+///   kind: web_socket
+///   endpoint: wss://example.com
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransportConfig {
+    Http { endpoint: String },
+    WebSocket { endpoint: String },
+}
+
+impl TransportConfig {
+    pub fn endpoint(&self) -> &str {
+        match self {
+            Self::Http { endpoint } | Self::WebSocket { endpoint } => endpoint,
+        }
+    }
+
+    pub fn is_web_socket(&self) -> bool {
+        matches!(self, Self::WebSocket { .. })
+    }
+}
+
+impl<'de> Deserialize<'de> for TransportConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(match TransportHelper::deserialize(deserializer)? {
+            TransportHelper::Url(endpoint)
+                if endpoint.starts_with("ws://") || endpoint.starts_with("wss://") =>
+            {
+                Self::WebSocket { endpoint }
+            }
+            TransportHelper::Url(endpoint) => Self::Http { endpoint },
+            TransportHelper::Explicit(ExplicitTransport::Http { endpoint }) => {
+                Self::Http { endpoint }
+            }
+            TransportHelper::Explicit(ExplicitTransport::WebSocket { endpoint }) => {
+                Self::WebSocket { endpoint }
+            }
+        })
+    }
+}
+
+impl JsonSchema for TransportConfig {
+    fn schema_name() -> String {
+        "TransportConfig".to_owned()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        TransportHelper::json_schema(gen)
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum BridgeProxiesHelper {
+    Single(#[schemars(with = "String")] Address),
+    Multiple(#[schemars(with = "Vec<String>")] Vec<Address>),
+}
+
+fn deserialize_bridge_proxies<'de, D>(deserializer: D) -> Result<Vec<Address>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match BridgeProxiesHelper::deserialize(deserializer)? {
+        BridgeProxiesHelper::Single(bridge_proxy) => vec![bridge_proxy],
+        BridgeProxiesHelper::Multiple(bridge_proxies) => bridge_proxies,
+    })
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum TransportHelper {
+    Url(String),
+    Explicit(ExplicitTransport),
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+enum ExplicitTransport {
+    Http { endpoint: String },
+    WebSocket { endpoint: String },
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum EndpointsHelper {
+    Single(TransportConfig),
+    Multiple(Vec<TransportConfig>),
+}
+
+fn deserialize_endpoints<'de, D>(deserializer: D) -> Result<Vec<TransportConfig>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let endpoints = match EndpointsHelper::deserialize(deserializer)? {
+        EndpointsHelper::Single(endpoint) => vec![endpoint],
+        EndpointsHelper::Multiple(endpoints) => endpoints,
+    };
+    if endpoints.is_empty() {
+        return Err(serde::de::Error::custom("at least one endpoint expected"));
+    }
+    Ok(endpoints)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    /// PEM encoded client certificate chain
+    pub cert_pem: String,
+
+    /// PEM encoded private key of the client certificate
+    pub key_pem: String,
+
+    /// PEM encoded CA certificate, trusted in addition to the built-in roots
+    #[serde(default)]
+    pub ca_pem: Option<String>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct RetryConfig {
+    /// Total number of attempts for each call. Default: 3
+    pub max_attempts: u32,
+
+    /// Delay before the first retry. Default: 200
+    pub base_delay_ms: u64,
+
+    /// Upper bound of the exponential backoff. Default: 5000
+    pub max_delay_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay_ms: 200,
+            max_delay_ms: 5000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TokenOverride {
+    /// Token address
+    #[schemars(with = "String")]
+    pub address: Address,
+
+    /// Chain id of the token network
+    pub chain_id: u32,
+
+    /// Token symbol
+    pub symbol: String,
+
+    /// Token decimals
+    pub decimals: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct StakingEntry {
+    /// Staking contract address
+    #[schemars(with = "String")]
+    pub address: Address,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct VaultsEntry {
     /// Vault address
+    #[schemars(with = "String")]
     pub address: Address,
 
+    /// Vault contract interface. Default: `ivault`
+    #[serde(default)]
+    pub vault_type: VaultType,
+
     /// Token group
     #[serde(default)]
     pub group: Option<String>,
+
+    /// Overrides the on-chain token symbol in the `symbol` label.
+    /// The first display name of the token in the config order is used
+    #[serde(default)]
+    pub display_name: Option<String>,
+
+    /// Vault polling interval in seconds. Default: `metrics_settings.collection_interval_sec`
+    #[serde(default)]
+    pub poll_interval_sec: Option<u64>,
+
+    /// Length of the vault withdrawal period in seconds. Default: 86400
+    #[serde(default = "default_withdrawal_period_sec")]
+    pub withdrawal_period_sec: u64,
+
+    /// Number of previous withdrawal periods to export as `withdrawal_period_history_*` metrics. Default: 0
+    #[serde(default)]
+    pub historical_periods: u32,
+
+    /// Weight of the latest value in the exponential moving average of the balance
+    /// and total assets (`0.0 < alpha <= 1.0`). Averages are not exported if not specified
+    #[serde(default)]
+    pub smoothing_alpha: Option<f64>,
+
+    /// Share of the vault in the consolidated TVL of its token. Default: 1.0
+    #[serde(default)]
+    pub weight: Option<f64>,
+
+    /// Whether to export governance related addresses (guardian, management, governance)
+    #[serde(default)]
+    pub track_governance: bool,
+
+    /// Whether to estimate gas of the `balanceOf` and `totalAssets` calls on each poll
+    #[serde(default)]
+    pub track_gas_estimates: bool,
+
+    /// Whether to find the vault deployment block at startup.
+    /// NOTE: requires an archive node
+    #[serde(default)]
+    pub scan_creation_block: bool,
+
+    /// Chainlink USD price feed of the vault token, used to export the vault TVL
+    #[serde(default)]
+    #[schemars(with = "Option<String>")]
+    pub price_feed: Option<Address>,
+
+    /// Vault token allowances to monitor
+    #[serde(default)]
+    pub monitored_allowances: Vec<AllowanceMonitor>,
+
+    /// Whether to count deposit and withdrawal events
+    #[serde(default)]
+    pub event_log_config: Option<EventLogConfig>,
+}
+
+impl VaultsEntry {
+    pub fn weight(&self) -> f64 {
+        self.weight.unwrap_or(1.0)
+    }
+}
+
+/// Vault contract interface
+///
+/// ```yaml
+/// vault_type: curve
+/// # or
+/// vault_type:
+///   aave_v3:
+///     pool_address: 0x87870bca3f3fd6335c3f4ce8392d69350b4fa4e2
+/// ```
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VaultType {
+    /// Bridge vault (`IVault.json`)
+    #[default]
+    #[serde(rename = "ivault")]
+    IVault,
+    /// Curve pool. The first pool coin is used as the vault token
+    Curve,
+    /// Bridge vault which supplies its token to the Aave v3 lending pool
+    AaveV3 {
+        #[schemars(with = "String")]
+        pool_address: Address,
+    },
+    /// Compound cToken. Its underlying token is used as the vault token
+    Compound,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EventLogConfig {
+    /// Number of recent blocks to scan at startup.
+    /// Also limits the range of each subsequent scan
+    pub lookback_blocks: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AllowanceMonitor {
+    /// Tokens owner
+    #[schemars(with = "String")]
+    pub owner: Address,
+
+    /// Allowed spender
+    #[schemars(with = "String")]
+    pub spender: Address,
+
+    /// Minimal expected allowance (decimal string in token units)
+    pub min_threshold: String,
+}
+
+fn default_metrics_listen_address() -> SocketAddr {
+    SocketAddr::from(([0, 0, 0, 0], 10000))
+}
+
+fn default_metrics_path() -> String {
+    "/".to_owned()
+}
+
+fn default_collection_interval_sec() -> u64 {
+    10
+}
+
+fn default_update_duration_buckets() -> Vec<f64> {
+    vec![0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]
+}
+
+fn default_progress_report_interval_secs() -> u64 {
+    10
+}
+
+fn default_push_interval_sec() -> u64 {
+    15
+}
+
+fn default_checksum_addresses() -> bool {
+    false
+}
+
+fn default_stale_state_threshold_sec() -> u32 {
+    300
+}
+
+fn default_max_concurrent_polls() -> usize {
+    16
+}
+
+fn default_call_timeout_ms() -> u64 {
+    10000
+}
+
+fn default_use_batching() -> bool {
+    true
+}
+
+fn default_monitor_peer_count() -> bool {
+    true
+}
+
+fn default_withdrawal_period_sec() -> u64 {
+    86400
 }
 
 fn default_logger_settings() -> serde_yaml::Value {
@@ -67,3 +903,81 @@ fn default_logger_settings() -> serde_yaml::Value {
     "##;
     serde_yaml::from_str(DEFAULT_LOG4RS_SETTINGS).unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BRIDGE_PROXY_1: &str = "0xf4404070f63a7e19be0b1dd89a5fb88e12c0173a";
+    const BRIDGE_PROXY_2: &str = "0x032d06b4cc8a914b85615acd0131c3e0a7330968";
+
+    fn parse_network(bridge_proxies: &str) -> NetworkVaults {
+        let yaml = format!("endpoint: http://127.0.0.1:8545\n{bridge_proxies}\nvaults: []\n");
+        serde_yaml::from_str(&yaml).unwrap()
+    }
+
+    fn address(address: &str) -> Address {
+        address.parse().unwrap()
+    }
+
+    #[test]
+    fn single_bridge_proxy() {
+        let network = parse_network(&format!("bridge_proxy: {BRIDGE_PROXY_1}"));
+        assert_eq!(network.bridge_proxies, vec![address(BRIDGE_PROXY_1)]);
+    }
+
+    #[test]
+    fn multiple_bridge_proxies() {
+        let network = parse_network(&format!(
+            "bridge_proxies:\n  - {BRIDGE_PROXY_1}\n  - {BRIDGE_PROXY_2}"
+        ));
+        assert_eq!(
+            network.bridge_proxies,
+            vec![address(BRIDGE_PROXY_1), address(BRIDGE_PROXY_2)]
+        );
+
+        // Both keys accept both forms
+        let network = parse_network(&format!("bridge_proxy: [{BRIDGE_PROXY_1}]"));
+        assert_eq!(network.bridge_proxies, vec![address(BRIDGE_PROXY_1)]);
+        let network = parse_network(&format!("bridge_proxies: {BRIDGE_PROXY_2}"));
+        assert_eq!(network.bridge_proxies, vec![address(BRIDGE_PROXY_2)]);
+    }
+
+    #[test]
+    fn no_bridge_proxies() {
+        let network = parse_network("");
+        assert!(network.bridge_proxies.is_empty());
+    }
+
+    #[test]
+    fn duplicate_bridge_proxies() {
+        let yaml = format!(
+            "networks:\n  - endpoint: http://127.0.0.1:8545\n    bridge_proxies: [{BRIDGE_PROXY_1}, {BRIDGE_PROXY_1}]\n    vaults: []\nmetrics_settings: {{}}\n"
+        );
+        let config: Config = serde_yaml::from_str(&yaml).unwrap();
+        let error = config.validate().unwrap_err();
+        assert!(error.to_string().starts_with("Duplicate bridge proxy"));
+    }
+
+    #[test]
+    fn address_format() {
+        let parse = |yaml: &str| serde_yaml::from_str::<MetricsSettings>(yaml).unwrap();
+        assert_eq!(parse("{}").address_format(), AddressFormat::WithPrefix);
+        assert_eq!(
+            parse("checksum_addresses: true").address_format(),
+            AddressFormat::Checksum
+        );
+        assert_eq!(
+            parse("checksum_addresses: true\naddress_format: without_prefix").address_format(),
+            AddressFormat::WithoutPrefix
+        );
+    }
+
+    #[test]
+    fn zero_collection_interval() {
+        let yaml = "networks: []\nmetrics_settings:\n  collection_interval_sec: 0\n";
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let error = config.validate().unwrap_err();
+        assert_eq!(error.to_string(), "Invalid collection interval: 0");
+    }
+}
@@ -2,7 +2,10 @@ use once_cell::race::OnceBox;
 use web3::ethabi;
 
 macro_rules! contract_methods(
-    ($module:ident, $abi:ident, { $($name:literal => $function:ident),*$(,)? }) => {
+    (
+        $module:ident, $abi:ident, { $($name:literal => $function:ident),*$(,)? }
+        $(, events { $($event_name:literal => $event:ident),*$(,)? })?
+    ) => {
         pub mod $module {
             use super::*;
 
@@ -15,6 +18,16 @@ macro_rules! contract_methods(
                 static ABI: OnceBox<ethabi::Function> = OnceBox::new();
                 ABI.get_or_init(|| Box::new(abi().function($name).expect("Shouldn't fail").clone()))
             })*
+
+            /// All methods used by the exporter
+            pub fn methods() -> Vec<&'static ethabi::Function> {
+                vec![$($function()),*]
+            }
+
+            $($(pub fn $event() -> &'static ethabi::Event {
+                static ABI: OnceBox<ethabi::Event> = OnceBox::new();
+                ABI.get_or_init(|| Box::new(abi().event($event_name).expect("Shouldn't fail").clone()))
+            })*)?
         }
     }
 );
@@ -23,20 +36,78 @@ contract_methods!(erc_20, ERC_20_ABI, {
     "symbol" => symbol,
     "decimals" => decimals,
     "balanceOf" => balance_of,
+    "allowance" => allowance,
+    "totalSupply" => total_supply,
 });
 
 contract_methods!(vault, VAULT_ABI, {
     "token" => token,
     "totalAssets" => total_assets,
     "withdrawLimitPerPeriod" => withdraw_limit_per_period,
+    "depositLimit" => deposit_limit,
     "withdrawalPeriods" => withdrawal_periods,
+    "withdrawalQueue" => withdrawal_queue,
+    "guardian" => guardian,
+    "management" => management,
+    "governance" => governance,
+    "paused" => paused,
+    "convertToAssets" => convert_to_assets,
+    "managementFee" => management_fee,
+    "minDeposit" => min_deposit,
+    "minWithdraw" => min_withdraw,
+}, events {
+    "Deposit" => deposit,
+    "InstantWithdrawal" => instant_withdrawal,
+    "PendingWithdrawalCreated" => pending_withdrawal_created,
+    "PendingWithdrawalWithdraw" => pending_withdrawal_withdraw,
 });
 
 contract_methods!(bridge, BRIDGE_ABI, {
     "lastRound" => last_round,
     "rounds" => rounds,
+}, events {
+    "NewRound" => new_round,
+});
+
+contract_methods!(curve_vault, CURVE_POOL_ABI, {
+    "get_virtual_price" => get_virtual_price,
+    "balances" => balances,
+    "coins" => coins,
+});
+
+contract_methods!(aave_pool, AAVE_POOL_ABI, {
+    "getReserveData" => get_reserve_data,
+});
+
+contract_methods!(compound, COMPOUND_ABI, {
+    "exchangeRateStored" => exchange_rate_stored,
+    "totalBorrows" => total_borrows,
+    "getCash" => get_cash,
+    "underlying" => underlying,
+});
+
+contract_methods!(staking, STAKING_ABI, {
+    "totalStaked" => total_staked,
+    "minStake" => min_stake,
+    "slash_count" => slash_count,
+});
+
+contract_methods!(governance, GOVERNANCE_ABI, {
+    "proposalCount" => proposal_count,
+    "proposalState" => proposal_state,
+    "quorumVotes" => quorum_votes,
+});
+
+contract_methods!(chainlink, CHAINLINK_ABI, {
+    "latestRoundData" => latest_round_data,
 });
 
 static ERC_20_ABI: &str = include_str!("ERC20.json");
 static VAULT_ABI: &str = include_str!("IVault.json");
 static BRIDGE_ABI: &str = include_str!("Bridge.json");
+static CURVE_POOL_ABI: &str = include_str!("CurvePool.json");
+static AAVE_POOL_ABI: &str = include_str!("AavePool.json");
+static COMPOUND_ABI: &str = include_str!("Compound.json");
+static STAKING_ABI: &str = include_str!("Staking.json");
+static GOVERNANCE_ABI: &str = include_str!("Governance.json");
+static CHAINLINK_ABI: &str = include_str!("Chainlink.json");
@@ -0,0 +1,143 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use hyper::{Body, Method, Request, Response, StatusCode};
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+
+use crate::config::*;
+use crate::service::*;
+
+/// Shared liveness and readiness state
+pub struct HealthState {
+    settings: HealthSettings,
+    /// Set when all listeners have completed their first update
+    service: OnceCell<Arc<Service>>,
+}
+
+impl HealthState {
+    pub fn new(settings: HealthSettings) -> Self {
+        Self {
+            settings,
+            service: Default::default(),
+        }
+    }
+
+    pub fn set_service(&self, service: Arc<Service>) {
+        self.service.get_or_init(|| service);
+    }
+}
+
+pub async fn healthcheck_service(address: SocketAddr, state: Arc<HealthState>) {
+    let make_service = hyper::service::make_service_fn(move |_| {
+        let state = state.clone();
+        async move {
+            Ok::<_, Infallible>(hyper::service::service_fn(move |req| {
+                let response = handle_request(&state, req);
+                async move { Ok::<_, Infallible>(response) }
+            }))
+        }
+    });
+
+    let server = match hyper::Server::try_bind(&address) {
+        Ok(server) => server.serve(make_service),
+        Err(e) => {
+            log::error!("Failed to bind healthcheck service: {e:?}");
+            return;
+        }
+    };
+
+    if let Err(e) = server.await {
+        log::error!("Healthcheck service stopped: {e:?}");
+    }
+}
+
+fn handle_request(state: &HealthState, req: Request<Body>) -> Response<Body> {
+    if req.method() != Method::GET {
+        return empty_response(StatusCode::NOT_FOUND);
+    }
+
+    match (req.uri().path(), state.service.get()) {
+        ("/health", Some(_)) => json_response(StatusCode::OK, &Status::ok()),
+        ("/ready", Some(service)) => {
+            let stale_vaults = service
+                .stale_vaults(state.settings.max_stale_secs)
+                .into_iter()
+                .map(|item| StaleVaultInfo {
+                    chain_id: item.chain_id,
+                    vault: format!("0x{:x}", item.vault),
+                    stale_secs: item.stale_secs,
+                })
+                .collect::<Vec<_>>();
+
+            if stale_vaults.is_empty() {
+                json_response(StatusCode::OK, &Status::ok())
+            } else {
+                let status = Status {
+                    status: "stale",
+                    stale_vaults,
+                };
+                json_response(StatusCode::SERVICE_UNAVAILABLE, &status)
+            }
+        }
+        ("/debug/vaults", Some(service)) if state.settings.debug_endpoint => {
+            raw_json_response(StatusCode::OK, service.debug_vaults())
+        }
+        ("/health" | "/ready", None) => {
+            json_response(StatusCode::SERVICE_UNAVAILABLE, &Status::starting())
+        }
+        _ => empty_response(StatusCode::NOT_FOUND),
+    }
+}
+
+#[derive(Serialize)]
+struct Status {
+    status: &'static str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stale_vaults: Vec<StaleVaultInfo>,
+}
+
+impl Status {
+    fn ok() -> Self {
+        Self {
+            status: "ok",
+            stale_vaults: Vec::new(),
+        }
+    }
+
+    fn starting() -> Self {
+        Self {
+            status: "starting",
+            stale_vaults: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct StaleVaultInfo {
+    chain_id: u32,
+    vault: String,
+    stale_secs: u32,
+}
+
+fn empty_response(status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = status;
+    response
+}
+
+fn json_response<T: Serialize>(status: StatusCode, body: &T) -> Response<Body> {
+    let body = serde_json::to_vec(body).expect("Shouldn't fail");
+    raw_json_response(status, body)
+}
+
+fn raw_json_response(status: StatusCode, body: Vec<u8>) -> Response<Body> {
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    response.headers_mut().insert(
+        hyper::header::CONTENT_TYPE,
+        hyper::header::HeaderValue::from_static("application/json"),
+    );
+    response
+}
@@ -0,0 +1,61 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::metrics_buffer::*;
+
+/// Cumulative histogram of durations with fixed bucket boundaries (in seconds)
+pub struct Histogram {
+    buckets: Arc<[f64]>,
+    counts: Box<[AtomicU64]>,
+    sum_micros: AtomicU64,
+    count: AtomicU64,
+}
+
+impl Histogram {
+    pub fn new(buckets: Arc<[f64]>) -> Self {
+        let counts = buckets.iter().map(|_| AtomicU64::new(0)).collect();
+        Self {
+            buckets,
+            counts,
+            sum_micros: Default::default(),
+            count: Default::default(),
+        }
+    }
+
+    pub fn observe(&self, duration: Duration) {
+        let secs = duration.as_secs_f64();
+        for (bound, count) in self.buckets.iter().zip(self.counts.iter()) {
+            if secs <= *bound {
+                count.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.sum_micros
+            .fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Writes `<name>_bucket`, `<name>_sum` and `<name>_count` metrics.
+    /// `labels` are applied to each of them
+    pub fn write<'b, W, F>(&self, f: &mut W, name: &str, labels: F) -> std::fmt::Result
+    where
+        W: BeginMetricExt<'b>,
+        F: for<'a> Fn(MetricBuilder<'a, 'b>) -> MetricBuilder<'a, 'b>,
+    {
+        let count = self.count.load(Ordering::Relaxed);
+
+        let bucket_name = format!("{name}_bucket");
+        for (bound, bucket_count) in self.buckets.iter().zip(self.counts.iter()) {
+            labels(f.begin_metric(&bucket_name))
+                .label("le", bound)
+                .value(bucket_count.load(Ordering::Relaxed))?;
+        }
+        labels(f.begin_metric(&bucket_name))
+            .label("le", "+Inf")
+            .value(count)?;
+
+        let sum = self.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        labels(f.begin_metric(&format!("{name}_sum"))).value(sum)?;
+        labels(f.begin_metric(&format!("{name}_count"))).value(count)
+    }
+}
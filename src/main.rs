@@ -1,18 +1,36 @@
-use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::{Context, Result};
 use argh::FromArgs;
 use serde::Deserialize;
+use tokio_util::sync::CancellationToken;
 
+use self::alerts::*;
 use self::config::*;
+use self::health::*;
+use self::metrics_buffer::*;
+use self::push_gateway::*;
+use self::reporting::*;
+use self::secrets::*;
 use self::service::*;
+use self::watcher::*;
 
+mod alerts;
+mod annotations;
+mod chains;
 mod config;
 mod contracts;
-mod printed_num;
+mod health;
+mod histogram;
+mod metrics_buffer;
+mod push_gateway;
+mod relabel;
+mod reporting;
+mod secrets;
 mod service;
+mod watcher;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -21,41 +39,152 @@ async fn main() -> Result<()> {
 }
 
 async fn run(app: App) -> Result<()> {
-    let config: Config = read_config(app.config)?;
-    init_logger(&config.logger_settings)?;
+    if app.print_schema {
+        let schema = schemars::schema_for!(Config);
+        println!("{}", serde_json::to_string_pretty(&schema)?);
+        return Ok(());
+    }
+
+    let config_format = parse_config_format(&app.config, app.config_format.as_deref())?;
+    let config: Config = read_config(&app.config, config_format)?;
+    config.validate().context("Invalid config")?;
+    if app.validate_config {
+        println!("Config OK");
+        return Ok(());
+    }
+    init_logger(&config.logger_settings, config.logging.log_format)?;
+
+    if app.check_abi {
+        let mut failed = 0;
+        for check in check_abi(&config.networks).await? {
+            let status = match &check.result {
+                Ok(()) => "ok".to_owned(),
+                Err(e) => {
+                    failed += 1;
+                    format!("FAILED: {e:#}")
+                }
+            };
+            println!(
+                "chain_id={} contract=0x{:x} method={} {status}",
+                check.chain_id, check.contract, check.method
+            );
+        }
+        return match failed {
+            0 => Ok(()),
+            failed => Err(anyhow::anyhow!("{failed} calls failed")),
+        };
+    }
 
-    if let Some(healthcheck_address) = config.healthcheck_address {
-        tokio::spawn(healthcheck_service(healthcheck_address));
+    let health = Arc::new(HealthState::new(config.health_settings));
+    if let Some(healthcheck_address) = config
+        .healthcheck_address
+        .filter(|_| !app.dry_run && !app.once)
+    {
+        tokio::spawn(healthcheck_service(healthcheck_address, health.clone()));
     }
 
-    let service = Service::new(config.networks)
-        .await
-        .context("Failed to create service")?;
+    let service = Arc::new(
+        Service::new(config.networks, &config.tokens, &config.metrics_settings)
+            .await
+            .context("Failed to create service")?,
+    );
+
+    if app.once {
+        let succeeded = service.update_once().await;
+        print!("{}", service.metrics());
+        service.shutdown().await;
+        return if succeeded {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("Some updates failed"))
+        };
+    }
+
+    if app.dry_run {
+        for info in service.vault_infos() {
+            println!(
+                "chain_id={} vault=0x{:x} token=0x{:x} symbol={} decimals={}",
+                info.chain_id, info.vault, info.token, info.symbol, info.decimals
+            );
+        }
+        service.shutdown().await;
+        return Ok(());
+    }
 
-    let interval = Duration::from_secs(config.metrics_settings.collection_interval_sec);
+    let metrics_settings = config.metrics_settings.exporter()?;
+    let interval = Duration::from_secs(metrics_settings.collection_interval_sec);
     service.start_listening(interval).await?;
+    health.set_service(service.clone());
 
     log::info!(
         "Server is running on {} with interval {}s",
-        config.metrics_settings.listen_address,
+        metrics_settings.listen_address,
         interval.as_secs()
     );
 
-    let (_exporter, writer) = pomfrit::create_exporter(Some(config.metrics_settings)).await?;
+    if let Some(daily_report) = config.daily_report {
+        let generator = ReportGenerator::new(daily_report, interval)?;
+        tokio::spawn(generator.run(service.clone()));
+    }
+
+    if let Some(push_gateway) = config.push_gateway {
+        let push_gateway = PushGateway::new(push_gateway, metrics_settings.format)?;
+        tokio::spawn(push_gateway.run(service.clone()));
+    }
 
-    writer.spawn(move |buffer| {
-        buffer.write(service.metrics());
+    if !config.alerts.is_empty() {
+        let alerts = AlertEvaluator::new(config.alerts, interval)?;
+        tokio::spawn(alerts.run(service.clone()));
+    }
+
+    tokio::spawn({
+        let service = service.clone();
+        async move {
+            if let Err(e) = watch_config(app.config, config_format, service, interval).await {
+                log::error!("Config watcher stopped: {e:?}");
+            }
+        }
     });
 
-    futures::future::pending().await
-}
+    let buffer = Arc::new(MetricsBuffer::default());
+    let exporter_cancellation = CancellationToken::new();
+    let exporter = spawn_exporter(
+        &metrics_settings,
+        buffer.clone(),
+        exporter_cancellation.clone(),
+    )?;
 
-async fn healthcheck_service(address: SocketAddr) {
-    let listener = tokio::net::TcpListener::bind(address).await.unwrap();
-    loop {
-        if let Err(e) = listener.accept().await {
-            log::error!("Failed to accept healthcheck connection: {e:?}");
+    tokio::spawn({
+        let service = service.clone();
+        async move {
+            let mut interval = tokio::time::interval(interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                interval.tick().await;
+                buffer.write(service.metrics());
+            }
         }
+    });
+
+    wait_for_shutdown_signal().await?;
+    log::info!("Received shutdown signal, stopping");
+
+    service.shutdown().await;
+    // Stops metrics exporter server
+    exporter_cancellation.cancel();
+    exporter.await.ok();
+
+    log::info!("Stopped");
+    Ok(())
+}
+
+async fn wait_for_shutdown_signal() -> Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut sigterm = signal(SignalKind::terminate()).context("Failed to subscribe to SIGTERM")?;
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => result.context("Failed to listen SIGINT"),
+        _ = sigterm.recv() => Ok(()),
     }
 }
 
@@ -65,36 +194,105 @@ struct App {
     /// path to the application config
     #[argh(option, short = 'c', default = "PathBuf::from(\"config.yaml\")")]
     config: PathBuf,
+
+    /// config format: `yaml`, `toml` or `json`. Detected by the file extension by default
+    #[argh(option)]
+    config_format: Option<String>,
+
+    /// validate the config and exit
+    #[argh(switch)]
+    validate_config: bool,
+
+    /// print the config JSON schema and exit
+    #[argh(switch)]
+    print_schema: bool,
+
+    /// connect to all networks, print discovered vaults and exit
+    #[argh(switch)]
+    dry_run: bool,
+
+    /// update all metrics once, print them to stdout and exit
+    #[argh(switch)]
+    once: bool,
+
+    /// call all used contract methods with zero inputs, print results and exit
+    #[argh(switch)]
+    check_abi: bool,
 }
 
-fn read_config<P, T>(path: P) -> Result<T>
+fn parse_config_format(
+    path: &std::path::Path,
+    format: Option<&str>,
+) -> Result<::config::FileFormat> {
+    fn from_name(name: &str) -> Option<::config::FileFormat> {
+        Some(match name.to_lowercase().as_str() {
+            "yaml" | "yml" => ::config::FileFormat::Yaml,
+            "toml" => ::config::FileFormat::Toml,
+            "json" => ::config::FileFormat::Json,
+            _ => return None,
+        })
+    }
+
+    match format {
+        Some(format) => from_name(format)
+            .ok_or_else(|| InitError::UnknownConfigFormat(format.to_owned()).into()),
+        // Fallback to YAML for unknown extensions
+        None => Ok(path
+            .extension()
+            .and_then(|extension| from_name(extension.to_str()?))
+            .unwrap_or(::config::FileFormat::Yaml)),
+    }
+}
+
+fn read_config<P, T>(path: P, format: ::config::FileFormat) -> Result<T>
 where
     P: AsRef<std::path::Path>,
     for<'de> T: Deserialize<'de>,
 {
     let data = std::fs::read_to_string(path).context("Failed to read config")?;
     let re = regex::Regex::new(r"\$\{([a-zA-Z_][0-9a-zA-Z_]*)\}").unwrap();
+
+    // NOTE: substitutions are replaced with `0` to parse the resolver section,
+    // because it must be known before substituting anything
+    let secrets: SecretsConfig = parse_config(&re.replace_all(&data, "0"), format)?;
+    let resolver = SecretResolver::new(secrets.secret_resolver.as_ref())?;
+
     let result = re.replace_all(&data, |caps: &regex::Captures| {
-        match std::env::var(&caps[1]) {
-            Ok(value) => value,
-            Err(_) => {
-                eprintln!("WARN: Environment variable {} was not set", &caps[1]);
+        match resolver.resolve(&caps[1]) {
+            Some(value) => value,
+            None => {
+                eprintln!("WARN: Secret {} was not set", &caps[1]);
                 String::default()
             }
         }
     });
 
+    parse_config(&result, format)
+}
+
+fn parse_config<T>(data: &str, format: ::config::FileFormat) -> Result<T>
+where
+    for<'de> T: Deserialize<'de>,
+{
     let mut config = ::config::Config::new();
-    config.merge(::config::File::from_str(
-        result.as_ref(),
-        ::config::FileFormat::Yaml,
-    ))?;
+    config.merge(::config::File::from_str(data, format))?;
 
     config.try_into().context("Failed to parse config")
 }
 
-fn init_logger(initial_value: &serde_yaml::Value) -> Result<log4rs::Handle> {
-    let handle = log4rs::config::init_config(parse_logger_config(initial_value.clone())?)?;
+/// Part of the config which is read before substitutions
+#[derive(Deserialize)]
+struct SecretsConfig {
+    #[serde(default)]
+    secret_resolver: Option<SecretResolverConfig>,
+}
+
+fn init_logger(initial_value: &serde_yaml::Value, format: LogFormat) -> Result<log4rs::Handle> {
+    let config = match format {
+        LogFormat::Text => parse_logger_config(initial_value.clone())?,
+        LogFormat::Json => parse_json_logger_config(initial_value.clone())?,
+    };
+    let handle = log4rs::config::init_config(config)?;
     Ok(handle)
 }
 
@@ -113,8 +311,161 @@ fn parse_logger_config(value: serde_yaml::Value) -> Result<log4rs::Config> {
     Ok(config)
 }
 
+/// Builds a config with a single JSON stdout appender, keeping logger levels
+fn parse_json_logger_config(value: serde_yaml::Value) -> Result<log4rs::Config> {
+    use log4rs::append::console::ConsoleAppender;
+    use log4rs::config::{Appender, Logger, Root};
+
+    const APPENDER: &str = "stdout";
+
+    let config = serde_yaml::from_value::<log4rs::config::RawConfig>(value)?;
+
+    let stdout = ConsoleAppender::builder()
+        .encoder(Box::new(JsonLineEncoder))
+        .build();
+
+    let loggers = config.loggers().into_iter().map(|logger| {
+        let builder = Logger::builder().additive(logger.additive());
+        let builder = if logger.appenders().is_empty() {
+            builder
+        } else {
+            builder.appender(APPENDER)
+        };
+        builder.build(logger.name(), logger.level())
+    });
+
+    let root = config.root();
+    let root_builder = if root.appenders().is_empty() {
+        Root::builder()
+    } else {
+        Root::builder().appender(APPENDER)
+    };
+
+    let config = log4rs::Config::builder()
+        .appender(Appender::builder().build(APPENDER, Box::new(stdout)))
+        .loggers(loggers)
+        .build(root_builder.build(root.level()))?;
+    Ok(config)
+}
+
+/// Writes each record as `{"timestamp":"...","level":"...","target":"...","message":"..."}`
+#[derive(Debug)]
+struct JsonLineEncoder;
+
+impl log4rs::encode::Encode for JsonLineEncoder {
+    fn encode(&self, w: &mut dyn log4rs::encode::Write, record: &log::Record) -> Result<()> {
+        #[derive(serde::Serialize)]
+        struct Line<'a> {
+            timestamp: String,
+            level: log::Level,
+            target: &'a str,
+            message: String,
+        }
+
+        let line = Line {
+            timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            level: record.level(),
+            target: record.target(),
+            message: record.args().to_string(),
+        };
+        serde_json::to_writer(&mut *w, &line)?;
+        w.write_all(b"\n")?;
+        Ok(())
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 enum InitError {
     #[error("Errors found when deserializing the logger config")]
     Deserializing,
+    #[error("Unknown config format: {0}")]
+    UnknownConfigFormat(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use ::config::FileFormat;
+
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name)
+    }
+
+    fn read_fixture(name: &str, format: FileFormat) -> Config {
+        let config: Config = read_config(fixture(name), format).unwrap();
+        config.validate().unwrap();
+        config
+    }
+
+    #[test]
+    fn same_config_in_all_formats() {
+        let yaml = read_fixture("config.yaml", FileFormat::Yaml);
+        let toml = read_fixture("config.toml", FileFormat::Toml);
+        let json = read_fixture("config.json", FileFormat::Json);
+
+        assert_eq!(yaml.networks.len(), 1);
+        assert_eq!(yaml.networks[0].vaults[0].poll_interval_sec, Some(30));
+        assert_eq!(yaml.metrics_settings.collection_interval_sec, 15);
+
+        let yaml = format!("{yaml:?}");
+        assert_eq!(yaml, format!("{toml:?}"));
+        assert_eq!(yaml, format!("{json:?}"));
+    }
+
+    #[test]
+    fn substituted_numbers() {
+        std::env::set_var("INTERVAL_SEC", "30");
+        let config = read_fixture("config_env.yaml", FileFormat::Yaml);
+        assert_eq!(config.metrics_settings.collection_interval_sec, 30u64);
+        assert_eq!(config.metrics_settings.stagger_ms, Some(30));
+    }
+
+    #[test]
+    fn config_format_from_extension() {
+        let format = |path: &str| parse_config_format(Path::new(path), None).unwrap();
+        assert_eq!(format("config.yaml"), FileFormat::Yaml);
+        assert_eq!(format("config.yml"), FileFormat::Yaml);
+        assert_eq!(format("config.TOML"), FileFormat::Toml);
+        assert_eq!(format("config.json"), FileFormat::Json);
+    }
+
+    #[test]
+    fn config_format_fallback() {
+        let format = |path: &str| parse_config_format(Path::new(path), None).unwrap();
+        assert_eq!(format("config.conf"), FileFormat::Yaml);
+        assert_eq!(format("config"), FileFormat::Yaml);
+    }
+
+    #[test]
+    fn explicit_config_format() {
+        let path = Path::new("config.yaml");
+        assert_eq!(
+            parse_config_format(path, Some("json")).unwrap(),
+            FileFormat::Json
+        );
+        assert_eq!(
+            parse_config_format(path, Some("Toml")).unwrap(),
+            FileFormat::Toml
+        );
+    }
+
+    #[test]
+    fn unknown_config_format() {
+        let error = parse_config_format(Path::new("config.yaml"), Some("ini")).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<InitError>(),
+            Some(InitError::UnknownConfigFormat(format)) if format == "ini"
+        ));
+    }
+
+    #[test]
+    fn wrong_config_format() {
+        let result = read_config::<_, Config>(fixture("config.yaml"), FileFormat::Json);
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,373 @@
+use std::borrow::{Borrow, Cow};
+use std::convert::Infallible;
+use std::fmt::Write;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::*;
+use crate::relabel::rewrite_label;
+
+/// Starts a metric line on the formatter
+pub trait BeginMetricExt<'b> {
+    fn begin_metric<'a>(&'a mut self, name: &'a str) -> MetricBuilder<'a, 'b>;
+}
+
+impl<'b> BeginMetricExt<'b> for std::fmt::Formatter<'b> {
+    fn begin_metric<'a>(&'a mut self, name: &'a str) -> MetricBuilder<'a, 'b> {
+        MetricBuilder::new(self, name, &[])
+    }
+}
+
+/// Writes a single `name{label="value",...} value` line, labels are renamed or dropped
+/// according to `rewrites`. The first error is kept and returned from `value`
+pub struct MetricBuilder<'a, 'b> {
+    fmt: &'a mut std::fmt::Formatter<'b>,
+    name: &'a str,
+    rewrites: &'a [LabelRewrite],
+    result: std::fmt::Result,
+    has_labels: bool,
+}
+
+impl<'a, 'b> MetricBuilder<'a, 'b> {
+    pub fn new(
+        fmt: &'a mut std::fmt::Formatter<'b>,
+        name: &'a str,
+        rewrites: &'a [LabelRewrite],
+    ) -> Self {
+        let result = fmt.write_str(name);
+        Self {
+            fmt,
+            name,
+            rewrites,
+            result,
+            has_labels: false,
+        }
+    }
+
+    /// Skips the label if the value is `None`
+    pub fn label_opt<V>(self, name: &str, value: impl Borrow<Option<V>>) -> Self
+    where
+        V: std::fmt::Display,
+    {
+        match value.borrow() {
+            Some(value) => self.label(name, value),
+            None => self,
+        }
+    }
+
+    pub fn label<V>(mut self, name: &str, value: V) -> Self
+    where
+        V: std::fmt::Display,
+    {
+        let Some(name) = rewrite_label(self.rewrites, self.name, name) else {
+            return self;
+        };
+
+        let fmt = &mut *self.fmt;
+        let has_labels = self.has_labels;
+        self.result = self.result.and_then(|_| {
+            fmt.write_char(if has_labels { ',' } else { '{' })?;
+            write!(fmt, "{name}=\"")?;
+            write!(LabelValueWriter(&mut *fmt), "{value}")?;
+            fmt.write_char('"')
+        });
+        self.has_labels = true;
+        self
+    }
+
+    pub fn value<V>(self, value: V) -> std::fmt::Result
+    where
+        V: std::fmt::Display,
+    {
+        self.result?;
+        if self.has_labels {
+            self.fmt.write_char('}')?;
+        }
+        writeln!(self.fmt, " {value}")
+    }
+}
+
+/// Escapes `\`, `"` and new lines of the written label value
+struct LabelValueWriter<'a, W>(&'a mut W);
+
+impl<W: Write> Write for LabelValueWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        let mut rest = s;
+        while let Some(index) = rest.find(['\\', '"', '\n']) {
+            self.0.write_str(&rest[..index])?;
+            self.0.write_str(match rest.as_bytes()[index] {
+                b'\\' => "\\\\",
+                b'"' => "\\\"",
+                _ => "\\n",
+            })?;
+            rest = &rest[index + 1..];
+        }
+        self.0.write_str(rest)
+    }
+}
+
+/// Parses `name="value",...` labels of a rendered metric line, values are unescaped.
+/// Unterminated values are skipped
+pub fn parse_labels(labels: &str) -> impl Iterator<Item = (&str, Cow<'_, str>)> {
+    let mut rest = labels;
+    std::iter::from_fn(move || {
+        let (name, value) = rest.split_once("=\"")?;
+        let (value, tail) = split_label_value(value)?;
+        rest = tail.trim_start_matches(',');
+        Some((name, unescape_label_value(value)))
+    })
+}
+
+/// Splits an escaped label value (without the opening quote) at its closing quote
+fn split_label_value(value: &str) -> Option<(&str, &str)> {
+    let mut escaped = false;
+    for (index, char) in value.char_indices() {
+        match char {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some((&value[..index], &value[index + 1..])),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn unescape_label_value(value: &str) -> Cow<'_, str> {
+    if !value.contains('\\') {
+        return Cow::Borrowed(value);
+    }
+
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(char) = chars.next() {
+        match char {
+            '\\' => match chars.next() {
+                Some('n') => result.push('\n'),
+                Some(char) => result.push(char),
+                None => {}
+            },
+            char => result.push(char),
+        }
+    }
+    Cow::Owned(result)
+}
+
+/// Latest rendered metrics, served by the exporter
+#[derive(Default)]
+pub struct MetricsBuffer {
+    data: parking_lot::RwLock<String>,
+}
+
+impl MetricsBuffer {
+    /// Replaces the buffer contents. Metrics are rendered before taking the lock
+    pub fn write<T>(&self, metrics: T)
+    where
+        T: std::fmt::Display,
+    {
+        let data = metrics.to_string();
+        *self.data.write() = data;
+    }
+
+    pub fn get(&self) -> String {
+        self.data.read().clone()
+    }
+}
+
+/// Binds the metrics server and serves `GET <metrics_path>` until cancelled
+pub fn spawn_exporter(
+    config: &ExporterConfig,
+    buffer: Arc<MetricsBuffer>,
+    cancellation: CancellationToken,
+) -> Result<JoinHandle<()>> {
+    let server = hyper::Server::try_bind(&config.listen_address)
+        .context("Failed to bind metrics exporter server port")?;
+
+    let path = config.metrics_path.clone();
+    let format = config.format;
+    let make_service = hyper::service::make_service_fn(move |_| {
+        let path = path.clone();
+        let buffer = buffer.clone();
+        async move {
+            Ok::<_, Infallible>(hyper::service::service_fn(move |req| {
+                let response = handle_request(&path, format, &buffer, req);
+                async move { Ok::<_, Infallible>(response) }
+            }))
+        }
+    });
+
+    log::info!("Metrics exporter started");
+
+    Ok(tokio::spawn(async move {
+        let server = server
+            .serve(make_service)
+            .with_graceful_shutdown(cancellation.cancelled_owned());
+
+        match server.await {
+            Ok(()) => log::info!("Metrics exporter stopped"),
+            Err(e) => log::error!("Metrics exporter stopped: {e:?}"),
+        }
+    }))
+}
+
+fn handle_request(
+    path: &hyper::http::uri::PathAndQuery,
+    format: MetricsFormat,
+    buffer: &MetricsBuffer,
+    req: Request<Body>,
+) -> Response<Body> {
+    if req.method() != Method::GET || req.uri() != path.as_str() {
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = StatusCode::NOT_FOUND;
+        return response;
+    }
+
+    let mut response = Response::new(Body::from(buffer.get()));
+    response.headers_mut().insert(
+        hyper::header::CONTENT_TYPE,
+        hyper::header::HeaderValue::from_static(format.content_type()),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Renders a single metric line with the specified value
+    fn render<V: std::fmt::Display + Copy>(value: V) -> String {
+        struct Metric<V>(V);
+
+        impl<V: std::fmt::Display + Copy> std::fmt::Display for Metric<V> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.begin_metric("total_assets")
+                    .label("chain_id", 1)
+                    .label_opt("network", None::<&str>)
+                    .value(self.0)
+            }
+        }
+
+        Metric(value).to_string()
+    }
+
+    #[test]
+    fn large_integers_are_not_shortened() {
+        assert_eq!(
+            render(u64::MAX as u128 + 1),
+            "total_assets{chain_id=\"1\"} 18446744073709551616\n"
+        );
+        assert_eq!(
+            render(u128::MAX),
+            "total_assets{chain_id=\"1\"} 340282366920938463463374607431768211455\n"
+        );
+        assert_eq!(
+            render(i128::MIN),
+            "total_assets{chain_id=\"1\"} -170141183460469231731687303715884105728\n"
+        );
+    }
+
+    #[test]
+    fn label_values_are_escaped() {
+        struct Metric;
+
+        impl std::fmt::Display for Metric {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.begin_metric("token_decimals")
+                    .label("symbol", "a\\b\"c\nd")
+                    .label("token", "plain")
+                    .value(18)
+            }
+        }
+
+        let rendered = Metric.to_string();
+        assert_eq!(
+            rendered,
+            "token_decimals{symbol=\"a\\\\b\\\"c\\nd\",token=\"plain\"} 18\n"
+        );
+
+        let (_, labels) = rendered.split_once('{').unwrap();
+        assert_eq!(
+            parse_labels(labels).collect::<Vec<_>>(),
+            [("symbol", "a\\b\"c\nd".into()), ("token", "plain".into())]
+        );
+    }
+
+    #[test]
+    fn parses_labels() {
+        let labels = parse_labels("chain_id=\"1\",network=\"eth main\",vault=\"\"}");
+        assert_eq!(
+            labels.collect::<Vec<_>>(),
+            [
+                ("chain_id", "1".into()),
+                ("network", "eth main".into()),
+                ("vault", "".into())
+            ]
+        );
+
+        assert_eq!(parse_labels("}").count(), 0);
+        // Unterminated values are skipped
+        assert_eq!(
+            parse_labels("chain_id=\"1\",vault=\"0x").collect::<Vec<_>>(),
+            [("chain_id", "1".into())]
+        );
+    }
+
+    #[test]
+    fn large_floats_are_not_in_exponent_notation() {
+        assert_eq!(
+            render(1e20f64),
+            "total_assets{chain_id=\"1\"} 100000000000000000000\n"
+        );
+        assert_eq!(render(0.25f64), "total_assets{chain_id=\"1\"} 0.25\n");
+    }
+
+    #[test]
+    fn metric_without_labels() {
+        struct Metric;
+
+        impl std::fmt::Display for Metric {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.begin_metric("uptime_seconds").value(u64::MAX)
+            }
+        }
+
+        assert_eq!(Metric.to_string(), "uptime_seconds 18446744073709551615\n");
+    }
+
+    #[test]
+    fn content_type_follows_format() {
+        let path = "/metrics".parse().unwrap();
+        let buffer = MetricsBuffer::default();
+        let content_type = |format| {
+            let req = Request::get("/metrics").body(Body::empty()).unwrap();
+            let response = handle_request(&path, format, &buffer, req);
+            assert_eq!(response.status(), StatusCode::OK);
+            response.headers()[hyper::header::CONTENT_TYPE].clone()
+        };
+
+        assert_eq!(
+            content_type(MetricsFormat::Prometheus),
+            "text/plain; version=0.0.4; charset=utf-8"
+        );
+        assert_eq!(
+            content_type(MetricsFormat::OpenMetrics),
+            "application/openmetrics-text; version=1.0.0; charset=utf-8"
+        );
+    }
+
+    #[test]
+    fn unknown_path_is_not_found() {
+        let path = "/metrics".parse().unwrap();
+        let req = Request::get("/").body(Body::empty()).unwrap();
+        let response = handle_request(
+            &path,
+            MetricsFormat::Prometheus,
+            &MetricsBuffer::default(),
+            req,
+        );
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,117 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+
+use crate::config::*;
+use crate::service::*;
+
+const PUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Periodically pushes formatted metrics to the Prometheus push gateway
+pub struct PushGateway {
+    client: reqwest::Client,
+    url: url::Url,
+    push_interval: Duration,
+    format: MetricsFormat,
+}
+
+impl PushGateway {
+    pub fn new(config: PushGatewayConfig, format: MetricsFormat) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(PUSH_TIMEOUT)
+            .build()
+            .context("Failed to build push gateway client")?;
+
+        Ok(Self {
+            client,
+            url: config.push_url()?,
+            push_interval: Duration::from_secs(config.push_interval_sec),
+            format,
+        })
+    }
+
+    pub async fn run(self, service: Arc<Service>) {
+        log::info!(
+            "Pushing metrics to {} every {}s",
+            self.url,
+            self.push_interval.as_secs()
+        );
+
+        let mut interval = tokio::time::interval(self.push_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            interval.tick().await;
+
+            // NOTE: render before awaiting, metrics hold the listeners lock
+            let metrics = service.metrics().to_string();
+            if let Err(e) = self.push(metrics).await {
+                log::error!("Failed to push metrics: {e:?}");
+            }
+        }
+    }
+
+    /// Replaces metrics with the same names in the job group
+    async fn push(&self, metrics: String) -> Result<()> {
+        self.client
+            .post(self.url.clone())
+            .header(reqwest::header::CONTENT_TYPE, self.format.content_type())
+            .body(metrics)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use super::*;
+
+    /// Pushes the metrics to a local server and returns the received content type
+    async fn pushed_content_type(format: MetricsFormat) -> String {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let make_service = hyper::service::make_service_fn(move |_| {
+            let tx = tx.clone();
+            async move {
+                Ok::<_, Infallible>(hyper::service::service_fn(
+                    move |req: hyper::Request<hyper::Body>| {
+                        let content_type = req.headers()[hyper::header::CONTENT_TYPE].clone();
+                        tx.send((req.uri().path().to_owned(), content_type)).ok();
+                        async { Ok::<_, Infallible>(hyper::Response::new(hyper::Body::empty())) }
+                    },
+                ))
+            }
+        });
+        let server = hyper::Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let address = server.local_addr();
+        tokio::spawn(server);
+
+        let config = PushGatewayConfig {
+            url: format!("http://{address}"),
+            job: "exporter".to_owned(),
+            push_interval_sec: 15,
+        };
+        let push_gateway = PushGateway::new(config, format).unwrap();
+        push_gateway.push("up 1\n".to_owned()).await.unwrap();
+
+        let (path, content_type) = rx.recv().await.unwrap();
+        assert_eq!(path, "/metrics/job/exporter");
+        content_type.to_str().unwrap().to_owned()
+    }
+
+    #[tokio::test]
+    async fn content_type_follows_format() {
+        assert_eq!(
+            pushed_content_type(MetricsFormat::Prometheus).await,
+            MetricsFormat::Prometheus.content_type()
+        );
+        assert_eq!(
+            pushed_content_type(MetricsFormat::OpenMetrics).await,
+            "application/openmetrics-text; version=1.0.0; charset=utf-8"
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use crate::config::LabelRewrite;
+use crate::metrics_buffer::{BeginMetricExt, MetricBuilder};
+
+/// Formatter which renames or drops labels of the written metrics
+pub struct Relabeling<'a, 'b> {
+    fmt: &'a mut std::fmt::Formatter<'b>,
+    rewrites: &'a [LabelRewrite],
+}
+
+impl<'a, 'b> Relabeling<'a, 'b> {
+    pub fn new(fmt: &'a mut std::fmt::Formatter<'b>, rewrites: &'a [LabelRewrite]) -> Self {
+        Self { fmt, rewrites }
+    }
+
+    /// Writes already relabeled metrics as is
+    pub fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.fmt.write_str(s)
+    }
+}
+
+impl<'b> BeginMetricExt<'b> for Relabeling<'_, 'b> {
+    fn begin_metric<'a>(&'a mut self, name: &'a str) -> MetricBuilder<'a, 'b> {
+        MetricBuilder::new(self.fmt, name, self.rewrites)
+    }
+}
+
+/// Returns the new name of the metric label, `None` if the label is dropped
+pub fn rewrite_label<'a>(
+    rewrites: &'a [LabelRewrite],
+    metric: &str,
+    label: &'a str,
+) -> Option<&'a str> {
+    let rewrite = rewrites.iter().find(|rewrite| {
+        rewrite.label == label
+            && rewrite
+                .metric
+                .as_deref()
+                .is_none_or(|rewritten| rewritten == metric)
+    });
+    match rewrite {
+        Some(rewrite) if rewrite.replacement.is_empty() => None,
+        Some(rewrite) => Some(rewrite.replacement.as_str()),
+        None => Some(label),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_are_rewritten_while_rendering() {
+        struct Metrics(Vec<LabelRewrite>);
+
+        impl std::fmt::Display for Metrics {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let f = &mut Relabeling::new(f, &self.0);
+                f.begin_metric("total_assets")
+                    .label("chain_id", 1)
+                    .label("vault", "0x01")
+                    .label("group", "DAI")
+                    .value(10)?;
+                f.begin_metric("token_decimals")
+                    .label("chain_id", 1)
+                    .label("vault", "0x01")
+                    .value(18)
+            }
+        }
+
+        let rewrite = |metric: Option<&str>, label: &str, replacement: &str| LabelRewrite {
+            metric: metric.map(str::to_owned),
+            label: label.to_owned(),
+            replacement: replacement.to_owned(),
+        };
+        let metrics = Metrics(vec![
+            rewrite(None, "chain_id", "chain"),
+            rewrite(Some("total_assets"), "vault", ""),
+        ]);
+
+        assert_eq!(
+            metrics.to_string(),
+            "total_assets{chain=\"1\",group=\"DAI\"} 10\n\
+             token_decimals{chain=\"1\",vault=\"0x01\"} 18\n"
+        );
+    }
+}
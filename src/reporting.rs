@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use web3::ethabi::{Address, Uint};
+
+use crate::config::*;
+use crate::service::*;
+
+/// Collects vault values and writes a daily digest with min/max/current values
+pub struct ReportGenerator {
+    output_path: PathBuf,
+    /// Seconds since UTC midnight
+    report_time: u32,
+    sample_interval: Duration,
+    history: BTreeMap<(u32, Address), VaultHistory>,
+}
+
+impl ReportGenerator {
+    pub fn new(config: DailyReportConfig, sample_interval: Duration) -> Result<Self> {
+        let report_time = parse_report_time(&config.report_time_utc)
+            .with_context(|| format!("Invalid report time: {}", config.report_time_utc))?;
+
+        Ok(Self {
+            output_path: config.output_path,
+            report_time,
+            sample_interval,
+            history: Default::default(),
+        })
+    }
+
+    pub async fn run(mut self, service: Arc<Service>) {
+        let mut next_report_at = compute_next_report_at(now(), self.report_time);
+
+        log::info!(
+            "Daily report will be written to {} at {next_report_at}",
+            self.output_path.display()
+        );
+
+        loop {
+            tokio::time::sleep(self.sample_interval).await;
+
+            self.add_samples(service.vault_samples());
+
+            let now = now();
+            if now < next_report_at {
+                continue;
+            }
+
+            match self.write_report(now) {
+                Ok(()) => log::info!("Written daily report to {}", self.output_path.display()),
+                Err(e) => log::error!("Failed to write daily report: {e:?}"),
+            }
+
+            self.history.clear();
+            next_report_at = compute_next_report_at(now, self.report_time);
+        }
+    }
+
+    fn add_samples(&mut self, samples: Vec<VaultSample>) {
+        for sample in samples {
+            let history = self
+                .history
+                .entry((sample.chain_id, sample.vault))
+                .or_insert_with(|| VaultHistory {
+                    token: sample.token,
+                    symbol: sample.symbol.clone(),
+                    updated_at: 0,
+                    values: Default::default(),
+                });
+
+            history.updated_at = sample.updated_at;
+            for (name, value) in sample.values {
+                history
+                    .values
+                    .entry(name)
+                    .and_modify(|range| range.add(value))
+                    .or_insert(ValueRange {
+                        min: value,
+                        max: value,
+                        current: value,
+                    });
+            }
+        }
+    }
+
+    fn write_report(&self, generated_at: u32) -> Result<()> {
+        let report = Report {
+            generated_at,
+            vaults: self
+                .history
+                .iter()
+                .map(|(&(chain_id, vault), history)| VaultReport {
+                    chain_id,
+                    vault: format!("0x{vault:x}"),
+                    token: format!("0x{:x}", history.token),
+                    symbol: &history.symbol,
+                    updated_at: history.updated_at,
+                    metrics: history
+                        .values
+                        .iter()
+                        .map(|(&name, range)| {
+                            let range = ValueRangeReport {
+                                min: range.min.to_string(),
+                                max: range.max.to_string(),
+                                current: range.current.to_string(),
+                            };
+                            (name, range)
+                        })
+                        .collect(),
+                })
+                .collect(),
+        };
+
+        let data = serde_json::to_vec_pretty(&report).context("Failed to serialize report")?;
+
+        let mut tmp_path = self.output_path.clone().into_os_string();
+        tmp_path.push(".tmp");
+
+        std::fs::write(&tmp_path, data).context("Failed to write temp report file")?;
+        std::fs::rename(&tmp_path, &self.output_path).context("Failed to replace report file")?;
+        Ok(())
+    }
+}
+
+struct VaultHistory {
+    token: Address,
+    symbol: String,
+    updated_at: u32,
+    values: BTreeMap<&'static str, ValueRange>,
+}
+
+struct ValueRange {
+    min: Uint,
+    max: Uint,
+    current: Uint,
+}
+
+impl ValueRange {
+    fn add(&mut self, value: Uint) {
+        self.min = std::cmp::min(self.min, value);
+        self.max = std::cmp::max(self.max, value);
+        self.current = value;
+    }
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    generated_at: u32,
+    vaults: Vec<VaultReport<'a>>,
+}
+
+#[derive(Serialize)]
+struct VaultReport<'a> {
+    chain_id: u32,
+    vault: String,
+    token: String,
+    symbol: &'a str,
+    updated_at: u32,
+    metrics: BTreeMap<&'static str, ValueRangeReport>,
+}
+
+#[derive(Serialize)]
+struct ValueRangeReport {
+    min: String,
+    max: String,
+    current: String,
+}
+
+fn parse_report_time(time: &str) -> Option<u32> {
+    let (hours, minutes) = time.split_once(':')?;
+    let hours: u32 = hours.parse().ok()?;
+    let minutes: u32 = minutes.parse().ok()?;
+    (hours < 24 && minutes < 60).then_some(hours * 3600 + minutes * 60)
+}
+
+fn compute_next_report_at(now: u32, report_time: u32) -> u32 {
+    let report_at = now - now % 86400 + report_time;
+    if report_at <= now {
+        report_at + 86400
+    } else {
+        report_at
+    }
+}
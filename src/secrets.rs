@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+
+use crate::config::*;
+
+const VAULT_AGENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Resolves `${NAME}` substitutions of the config
+pub enum SecretResolver {
+    Env,
+    /// Secrets which were loaded once from a file or Vault
+    Loaded(HashMap<String, String>),
+}
+
+impl SecretResolver {
+    pub fn new(config: Option<&SecretResolverConfig>) -> Result<Self> {
+        Ok(match config {
+            None | Some(SecretResolverConfig::Env) => Self::Env,
+            Some(SecretResolverConfig::File { path }) => Self::Loaded(
+                read_secrets_file(path)
+                    .with_context(|| format!("Failed to read secrets from {}", path.display()))?,
+            ),
+            Some(SecretResolverConfig::VaultAgent {
+                socket,
+                secret_path,
+            }) => Self::Loaded(
+                read_vault_secret(socket, secret_path, VAULT_AGENT_TIMEOUT)
+                    .with_context(|| format!("Failed to read Vault secret {secret_path}"))?,
+            ),
+        })
+    }
+
+    pub fn resolve(&self, name: &str) -> Option<String> {
+        match self {
+            Self::Env => std::env::var(name).ok(),
+            Self::Loaded(secrets) => secrets.get(name).cloned(),
+        }
+    }
+}
+
+fn read_secrets_file(path: &Path) -> Result<HashMap<String, String>> {
+    let data = std::fs::read_to_string(path)?;
+
+    let mut secrets = HashMap::new();
+    for line in data.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (name, value) = line
+            .split_once('=')
+            .with_context(|| format!("Invalid secrets line: {line}"))?;
+        secrets.insert(name.trim().to_owned(), value.trim().to_owned());
+    }
+    Ok(secrets)
+}
+
+/// NOTE: the agent must be configured with `use_auto_auth_token`,
+/// so requests are sent without a token
+fn read_vault_secret(
+    socket: &Path,
+    secret_path: &str,
+    timeout: Duration,
+) -> Result<HashMap<String, String>> {
+    let mut stream = std::os::unix::net::UnixStream::connect(socket)
+        .with_context(|| format!("Failed to connect to {}", socket.display()))?;
+    // NOTE: a stuck agent must not block the config reload forever
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+
+    // NOTE: HTTP/1.0 responses are neither chunked nor kept alive
+    let secret_path = secret_path.trim_start_matches('/');
+    write!(
+        stream,
+        "GET /v1/{secret_path} HTTP/1.0\r\nHost: localhost\r\nAccept: application/json\r\n\r\n"
+    )?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .context("Invalid HTTP response")?;
+    let status = head.split_whitespace().nth(1).unwrap_or_default();
+    if status != "200" {
+        anyhow::bail!("Unexpected response status: {status}");
+    }
+
+    let body = serde_json::from_str::<VaultResponse>(body)?;
+    // NOTE: KV v2 secrets are nested in `data.data`
+    let data = match body.data.get("data") {
+        Some(serde_json::Value::Object(data)) => data.clone(),
+        _ => body.data,
+    };
+
+    Ok(data
+        .into_iter()
+        .filter_map(|(name, value)| match value {
+            serde_json::Value::String(value) => Some((name, value)),
+            _ => None,
+        })
+        .collect())
+}
+
+#[derive(serde::Deserialize)]
+struct VaultResponse {
+    data: serde_json::Map<String, serde_json::Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::net::UnixListener;
+
+    use super::*;
+
+    /// Serves a single connection with the response, returns the socket path
+    fn serve_once(name: &str, response: Option<&'static str>) -> std::path::PathBuf {
+        let socket = std::env::temp_dir().join(format!(
+            "bridge-vaults-exporter-{name}-{}.sock",
+            std::process::id()
+        ));
+        std::fs::remove_file(&socket).ok();
+
+        let listener = UnixListener::bind(&socket).unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            match response {
+                Some(response) => stream.write_all(response.as_bytes()).unwrap(),
+                // Keep the connection open without responding
+                None => std::thread::sleep(Duration::from_secs(5)),
+            }
+        });
+        socket
+    }
+
+    #[test]
+    fn reads_kv_v2_secret() {
+        let socket = serve_once(
+            "kv2",
+            Some(concat!(
+                "HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n",
+                r#"{"data":{"data":{"RPC_URL":"http://node","RETRIES":3},"metadata":{}}}"#
+            )),
+        );
+
+        let secrets = read_vault_secret(&socket, "/secret/data/exporter", VAULT_AGENT_TIMEOUT);
+        std::fs::remove_file(&socket).ok();
+        let secrets = secrets.unwrap();
+        assert_eq!(secrets.len(), 1);
+        assert_eq!(secrets["RPC_URL"], "http://node");
+    }
+
+    #[test]
+    fn fails_on_error_status() {
+        let socket = serve_once("status", Some("HTTP/1.0 403 Forbidden\r\n\r\n{}"));
+
+        let result = read_vault_secret(&socket, "secret/exporter", VAULT_AGENT_TIMEOUT);
+        std::fs::remove_file(&socket).ok();
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Unexpected response status: 403"
+        );
+    }
+
+    #[test]
+    fn stuck_agent_times_out() {
+        let socket = serve_once("stuck", None);
+
+        let started_at = std::time::Instant::now();
+        let result = read_vault_secret(&socket, "secret/exporter", Duration::from_millis(100));
+        std::fs::remove_file(&socket).ok();
+        assert!(result.is_err());
+        assert!(started_at.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,87 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use notify::{EventKind, RecursiveMode, Watcher};
+
+use crate::config::*;
+use crate::service::*;
+
+/// Reloads networks config on config file changes
+pub async fn watch_config(
+    path: PathBuf,
+    format: ::config::FileFormat,
+    service: Arc<Service>,
+    interval: Duration,
+) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .context("Invalid config path")?
+        .to_os_string();
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    let (events_tx, mut events_rx) = tokio::sync::mpsc::unbounded_channel();
+
+    let mut watcher =
+        notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+            Ok(event)
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                    && event
+                        .paths
+                        .iter()
+                        .any(|path| path.file_name() == Some(file_name.as_os_str())) =>
+            {
+                events_tx.send(()).ok();
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Config watcher error: {e:?}"),
+        })
+        .context("Failed to create config watcher")?;
+
+    // NOTE: watch the whole directory because editors usually replace the file
+    watcher
+        .watch(&directory, RecursiveMode::NonRecursive)
+        .context("Failed to watch config directory")?;
+
+    log::info!("Watching config changes at {}", path.display());
+
+    while events_rx.recv().await.is_some() {
+        // Wait until the file is completely written
+        tokio::time::sleep(CONFIG_DEBOUNCE_INTERVAL).await;
+        while events_rx.try_recv().is_ok() {}
+
+        // NOTE: secrets are read with blocking IO
+        let config = tokio::task::spawn_blocking({
+            let path = path.clone();
+            move || crate::read_config::<_, Config>(&path, format)
+        })
+        .await
+        .context("Config reader panicked")
+        .and_then(|config| config)
+        .and_then(|config| config.validate().map(|_| config).context("Invalid config"));
+        let result = match config {
+            Ok(config) => {
+                service
+                    .reload(config.networks, &config.tokens, interval)
+                    .await
+            }
+            Err(e) => {
+                service.register_failed_reload();
+                Err(e)
+            }
+        };
+
+        match result {
+            Ok(()) => log::info!("Reloaded config"),
+            Err(e) => log::error!("Failed to reload config: {e:?}"),
+        }
+    }
+
+    Ok(())
+}
+
+const CONFIG_DEBOUNCE_INTERVAL: Duration = Duration::from_millis(500);
@@ -0,0 +1,231 @@
+//! Runs the exporter against a stub JSON-RPC node and checks the exported metrics.
+//!
+//! Enabled with `cargo test --features integration-tests`
+#![cfg(feature = "integration-tests")]
+
+use std::convert::Infallible;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use hyper::{Body, Request, Response};
+use serde_json::{json, Value};
+use web3::ethabi::{Address, Token, Uint};
+
+const VAULT: &str = "0x032d06b4cc8a914b85615acd0131c3e0a7330968";
+const TOKEN: &str = "0x6b175474e89094c44da98b954eedeac495271d0f";
+
+#[tokio::test]
+async fn exports_vault_metrics() {
+    let node_address = spawn_stub_node();
+    let metrics_address = free_local_address();
+
+    let config_path = std::env::temp_dir().join(format!(
+        "bridge-vaults-exporter-{}.yaml",
+        std::process::id()
+    ));
+    std::fs::write(
+        &config_path,
+        format!(
+            r#"
+networks:
+  - endpoint: http://{node_address}
+    monitor_peer_count: false
+    vaults:
+      - address: {VAULT}
+metrics_settings:
+  listen_address: "{metrics_address}"
+  collection_interval_sec: 1
+  checksum_addresses: false
+"#
+        ),
+    )
+    .unwrap();
+
+    let _exporter = Exporter(
+        Command::new(env!("CARGO_BIN_EXE_bridge-vaults-exporter"))
+            .arg("--config")
+            .arg(&config_path)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap(),
+    );
+
+    let metrics = scrape_until(metrics_address, "total_assets{").await;
+    std::fs::remove_file(&config_path).ok();
+
+    let labels = format!(r#"chain_id="1",network_name="ethereum",vault="{VAULT}",token="{TOKEN}""#);
+    for line in [
+        format!("balance{{{labels}}} 500"),
+        format!("total_assets{{{labels}}} 1000"),
+        format!("withdraw_limit_per_period{{{labels}}} 300"),
+        format!("withdrawal_period_total{{{labels}}} 100"),
+        format!("withdrawal_period_considered{{{labels}}} 40"),
+    ] {
+        assert!(
+            metrics.lines().any(|item| item == line),
+            "`{line}` not found in:\n{metrics}"
+        );
+    }
+    assert!(metrics.contains(&format!(r#"token="{TOKEN}",symbol="DAI"}} 18"#)));
+}
+
+/// Kills the exporter process when the test ends
+struct Exporter(Child);
+
+impl Drop for Exporter {
+    fn drop(&mut self) {
+        self.0.kill().ok();
+        self.0.wait().ok();
+    }
+}
+
+fn free_local_address() -> SocketAddr {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+}
+
+async fn scrape_until(address: SocketAddr, pattern: &str) -> String {
+    let started_at = Instant::now();
+    loop {
+        if let Ok(metrics) = scrape(address) {
+            if metrics.contains(pattern) {
+                return metrics;
+            }
+        }
+
+        assert!(
+            started_at.elapsed() < Duration::from_secs(30),
+            "Metrics were not exported in time"
+        );
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+}
+
+fn scrape(address: SocketAddr) -> std::io::Result<String> {
+    let mut stream = TcpStream::connect(address)?;
+    write!(stream, "GET / HTTP/1.0\r\nHost: {address}\r\n\r\n")?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    Ok(match response.split_once("\r\n\r\n") {
+        Some((_, body)) => body.to_owned(),
+        None => String::new(),
+    })
+}
+
+fn spawn_stub_node() -> SocketAddr {
+    let make_service = hyper::service::make_service_fn(|_| async {
+        Ok::<_, Infallible>(hyper::service::service_fn(|req: Request<Body>| async {
+            let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+            let response = match serde_json::from_slice::<Value>(&body).unwrap() {
+                Value::Array(requests) => requests.iter().map(handle_request).collect(),
+                request => handle_request(&request),
+            };
+            Ok::<_, Infallible>(Response::new(Body::from(response.to_string())))
+        }))
+    });
+
+    let server = hyper::Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(make_service);
+    let address = server.local_addr();
+    tokio::spawn(server);
+    address
+}
+
+fn handle_request(request: &Value) -> Value {
+    let id = request["id"].clone();
+    let params = &request["params"];
+    let result = match request["method"].as_str().unwrap_or_default() {
+        "eth_chainId" => json!("0x1"),
+        "eth_blockNumber" => json!("0x3e8"),
+        "eth_gasPrice" => json!("0x6fc23ac00"),
+        "eth_syncing" => json!(false),
+        "eth_getCode" => json!("0x6000"),
+        "eth_getStorageAt" => json!(format!("0x{}", "0".repeat(64))),
+        "eth_getBlockByNumber" => block(),
+        "eth_call" => {
+            let data = params[0]["data"]
+                .as_str()
+                .or_else(|| params[0]["input"].as_str())
+                .unwrap_or_default();
+            match call_output(data.trim_start_matches("0x").get(..8).unwrap_or_default()) {
+                Some(tokens) => json!(format!("0x{}", hex(&web3::ethabi::encode(&tokens)))),
+                None => {
+                    return json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": 3, "message": "execution reverted" },
+                    })
+                }
+            }
+        }
+        _ => {
+            return json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": -32601, "message": "method not found" },
+            })
+        }
+    };
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+/// Outputs of the vault and token methods by selector, other methods revert
+fn call_output(selector: &str) -> Option<Vec<Token>> {
+    let uint = |value: u64| Token::Uint(Uint::from(value));
+    let outputs = [
+        ("token()", Token::Address(TOKEN.parse().unwrap())),
+        ("symbol()", Token::String("DAI".to_owned())),
+        ("decimals()", uint(18)),
+        ("balanceOf(address)", uint(500)),
+        ("totalSupply()", uint(10_000)),
+        ("totalAssets()", uint(1000)),
+        ("withdrawLimitPerPeriod()", uint(300)),
+        ("depositLimit()", uint(5000)),
+        (
+            "withdrawalPeriods(uint256)",
+            Token::Tuple(vec![uint(100), uint(40)]),
+        ),
+        (
+            "withdrawalQueue()",
+            Token::FixedArray(vec![Token::Address(Address::zero()); 20]),
+        ),
+    ];
+    outputs
+        .into_iter()
+        .find(|(signature, _)| {
+            hex(&web3::signing::keccak256(signature.as_bytes())[..4]) == selector
+        })
+        .map(|(_, output)| vec![output])
+}
+
+fn block() -> Value {
+    let hash = format!("0x{}", "11".repeat(32));
+    json!({
+        "number": "0x3e8",
+        "hash": hash,
+        "parentHash": hash,
+        "baseFeePerGas": "0x2cb417800",
+        "timestamp": "0x6553f100",
+        "transactions": [],
+        "uncles": [],
+        "gasLimit": "0x1",
+        "gasUsed": "0x1",
+        "miner": format!("0x{}", "00".repeat(20)),
+        "stateRoot": hash,
+        "transactionsRoot": hash,
+        "receiptsRoot": hash,
+        "logsBloom": format!("0x{}", "00".repeat(256)),
+        "extraData": "0x",
+        "sha3Uncles": hash,
+        "difficulty": "0x0",
+        "size": "0x1",
+    })
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}